@@ -0,0 +1,42 @@
+//! Scans generated sources of increasing size and prints the throughput of each.
+//! Run with `cargo bench --bench scanner`: the time per megabyte should stay flat
+//! as the input grows, which it can only do if scanning is linear.
+
+use std::{hint::black_box, time::Instant};
+
+use lox_lang::scanner::Scanner;
+
+const CHUNK: &str = r#"// Generated code with a bit of everything the scanner handles.
+fun fib(n) {
+  if (n <= 1) return n;
+  var label = "naïve → 日本語 strings";
+  return fib(n - 2) + fib(n - 1) * 3.25 / 7;
+}
+class Point < Base { init(x, y) { this.x = x; this.y = y != nil ? y : 0; } }
+"#;
+
+fn source_of_size(bytes: usize) -> String {
+    CHUNK.repeat(bytes / CHUNK.len() + 1)
+}
+
+fn main() {
+    let mut baseline = None;
+    for megabytes in [1, 2, 4, 8] {
+        let source = source_of_size(megabytes * 1024 * 1024);
+
+        let started = Instant::now();
+        let tokens = Scanner::new(black_box(source)).scan_tokens();
+        let elapsed = started.elapsed();
+        black_box(tokens);
+
+        let per_megabyte = elapsed.as_secs_f64() * 1000.0 / megabytes as f64;
+        let baseline = *baseline.get_or_insert(per_megabyte);
+        println!(
+            "{:>2} MB: {:>8.2} ms total, {:>6.2} ms/MB ({:.2}x the 1 MB rate)",
+            megabytes,
+            elapsed.as_secs_f64() * 1000.0,
+            per_megabyte,
+            per_megabyte / baseline
+        );
+    }
+}
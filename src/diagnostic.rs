@@ -0,0 +1,340 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, IsTerminal},
+};
+
+use crate::token::{Token, TokenType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    fn color(&self) -> &'static str {
+        match self {
+            Severity::Error => "31",
+            Severity::Warning => "33",
+            Severity::Note => "36",
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// The pipeline stage that reported a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Scan,
+    Parse,
+    Resolve,
+    Runtime,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Scan => write!(f, "scan"),
+            Phase::Parse => write!(f, "parse"),
+            Phase::Resolve => write!(f, "resolve"),
+            Phase::Runtime => write!(f, "runtime"),
+        }
+    }
+}
+
+/// How diagnostics are written out by the command-line driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticFormat {
+    /// Rendered source snippets, see [`Diagnostic::render`].
+    Human,
+    /// One JSON object per line, see [`Diagnostic::to_json`].
+    Json,
+}
+
+/// A source location to underline: `length` characters starting at `column` on `line`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(line: usize, column: usize, length: usize, message: String) -> Self {
+        Self {
+            line,
+            column,
+            length,
+            message,
+        }
+    }
+
+    pub fn at_token(token: &Token, message: String) -> Self {
+        let length = token.lexeme.lines().next().unwrap_or("").chars().count();
+        Label::new(token.line, token.span.column, length, message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub phase: Phase,
+    pub message: String,
+    /// What the error points at, e.g. `at 'x'` or `at end`.
+    pub location: String,
+    pub primary: Label,
+    pub secondary: Option<Label>,
+    /// Extra lines printed after the source snippet, such as a call-stack trace.
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        phase: Phase,
+        message: String,
+        location: String,
+        primary: Label,
+    ) -> Self {
+        Self {
+            severity,
+            phase,
+            message,
+            location,
+            primary,
+            secondary: None,
+            notes: vec![],
+        }
+    }
+
+    pub fn error_at_token(phase: Phase, token: &Token, message: String) -> Self {
+        let location = if token.token_type == TokenType::EOF {
+            "at end".to_string()
+        } else {
+            format!("at '{}'", token.lexeme)
+        };
+        Diagnostic::new(
+            Severity::Error,
+            phase,
+            message,
+            location,
+            Label::at_token(token, "".to_string()),
+        )
+    }
+
+    pub fn with_secondary(mut self, label: Label) -> Self {
+        self.secondary = Some(label);
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    /// Renders the diagnostic with the offending source line(s) and a caret under the span.
+    pub fn render(&self, source: &str, color: bool) -> String {
+        let paint = |text: &str, code: &str| {
+            if color {
+                format!("\x1b[1;{}m{}\x1b[0m", code, text)
+            } else {
+                text.to_string()
+            }
+        };
+
+        let mut header = paint(&self.severity.to_string(), self.severity.color());
+        if !self.location.is_empty() {
+            header = header + " " + &self.location;
+        }
+        let mut out = format!("{}: {}\n", header, self.message);
+
+        let mut labels = vec![(&self.primary, '^', self.severity.color())];
+        if let Some(secondary) = &self.secondary {
+            labels.push((secondary, '-', "34"));
+        }
+        labels.sort_by_key(|(label, _, _)| (label.line, label.column));
+
+        let gutter = labels
+            .iter()
+            .map(|(label, _, _)| label.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let bar = paint("|", "34");
+
+        out += &format!(
+            "{:gutter$}{} line {}, column {}\n",
+            "",
+            paint("-->", "34"),
+            self.primary.line,
+            self.primary.column
+        );
+
+        let lines: Vec<&str> = source.lines().collect();
+        let mut rendered_line = None;
+        for (label, marker, code) in labels {
+            let Some(text) = label.line.checked_sub(1).and_then(|idx| lines.get(idx)) else {
+                continue;
+            };
+            if rendered_line != Some(label.line) {
+                match rendered_line {
+                    None => out += &format!("{:gutter$} {}\n", "", bar),
+                    Some(previous) if label.line > previous + 1 => {
+                        out += &format!("{:gutter$}{}\n", "", paint("...", "34"))
+                    }
+                    Some(_) => {}
+                }
+                out += &format!(
+                    "{:>gutter$} {} {}\n",
+                    label.line,
+                    bar,
+                    text.replace('\t', "    ")
+                );
+                rendered_line = Some(label.line);
+            }
+
+            // Tabs were expanded above, so measure the indent the same way.
+            let indent: usize = text
+                .chars()
+                .take(label.column.saturating_sub(1))
+                .map(|c| if c == '\t' { 4 } else { 1 })
+                .sum();
+            let underline = marker.to_string().repeat(label.length.max(1));
+            let mut marker_line = paint(&underline, code);
+            if !label.message.is_empty() {
+                marker_line = marker_line + " " + &paint(&label.message, code);
+            }
+            out += &format!("{:gutter$} {} {:indent$}{}\n", "", bar, "", marker_line);
+        }
+
+        for note in &self.notes {
+            out += &format!("{}\n", note);
+        }
+
+        out
+    }
+
+    /// Serializes the diagnostic as a single-line JSON object for editors and CI tools. It
+    /// carries everything the human rendering shows: `notes` holds the extra lines such as a
+    /// call-stack trace, and `related` the secondary label, or `null` when there is none.
+    pub fn to_json(&self, file: &str) -> String {
+        let notes: Vec<String> = self
+            .notes
+            .iter()
+            .map(|note| format!("\"{}\"", escape_json(note.trim_start())))
+            .collect();
+        let related = match &self.secondary {
+            Some(label) => format!(
+                "{{\"line\":{},\"column\":{},\"message\":\"{}\"}}",
+                label.line,
+                label.column,
+                escape_json(&label.message)
+            ),
+            None => "null".to_string(),
+        };
+        format!(
+            "{{\"file\":\"{}\",\"line\":{},\"column\":{},\"severity\":\"{}\",\"phase\":\"{}\",\"message\":\"{}\",\"notes\":[{}],\"related\":{}}}",
+            escape_json(file),
+            self.primary.line,
+            self.primary.column,
+            self.severity,
+            self.phase,
+            escape_json(&self.message),
+            notes.join(","),
+            related
+        )
+    }
+}
+
+fn escape_json(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Every diagnostic reported while running a piece of source, in the order they were found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self { items: vec![] }
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.items.push(diagnostic);
+    }
+
+    pub fn extend(&mut self, other: Diagnostics) {
+        self.items.extend(other.items);
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.items
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.items.iter()
+    }
+
+    pub fn render(&self, source: &str, color: bool) -> String {
+        self.items
+            .iter()
+            .map(|diagnostic| diagnostic.render(source, color))
+            .collect()
+    }
+
+    /// One JSON object per diagnostic, each followed by a newline.
+    pub fn to_json_lines(&self, file: &str) -> String {
+        self.items
+            .iter()
+            .map(|diagnostic| diagnostic.to_json(file) + "\n")
+            .collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+/// Colour is only used when writing to a terminal and `NO_COLOR` is not set.
+pub fn use_color() -> bool {
+    io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
@@ -1,36 +1,64 @@
-use crate::token::{Token, TokenType};
+use crate::{
+    diagnostic::{Diagnostic, Phase},
+    token::Token,
+};
 
-pub struct LoxError;
-impl LoxError {
-    pub fn error(line: usize, message: String) {
-        LoxError::report(line, "".to_string(), message, &mut false);
-    }
+/// An active call: the function being run and the line it was called from.
+#[derive(Debug, Clone)]
+pub struct CallFrame {
+    pub function: String,
+    pub line: usize,
+}
 
-    pub fn report(line: usize, error_where: String, message: String, has_error: &mut bool) {
-        println!("[line {line}] Error {error_where} => {message} ");
-        *has_error = true;
-    }
-    pub fn token_errro(token: &Token, message: String) {
-        if token.token_type == TokenType::EOF {
-            LoxError::report(token.line, " at end".to_string(), message, &mut false);
-        } else {
-            LoxError::report(
-                token.line,
-                " at '".to_string() + &token.lexeme + "'",
-                message,
-                &mut false,
-            );
-        }
-    }
+/// The call stack at the point of an error, innermost call first.
+#[derive(Debug, Default)]
+pub struct StackTrace {
+    pub frames: Vec<CallFrame>,
 }
 
 #[derive(Debug)]
 pub struct RunTimeError {
     pub token: Token,
     pub message: String,
+    /// Boxed so the error stays small, since it is returned through every interpreter frame.
+    pub trace: Box<StackTrace>,
 }
 impl RunTimeError {
     pub fn new(token: Token, message: String) -> Self {
-        Self { token, message }
+        Self {
+            token,
+            message,
+            trace: Box::default(),
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let mut diagnostic =
+            Diagnostic::error_at_token(Phase::Runtime, &self.token, self.message.clone());
+        // Collapse runs of identical frames so deep recursion doesn't flood the output.
+        let mut i = 0;
+        while i < self.trace.frames.len() {
+            let frame = &self.trace.frames[i];
+            diagnostic =
+                diagnostic.with_note(format!("    in {}() [line {}]", frame.function, frame.line));
+
+            let mut repeated = 0;
+            while self
+                .trace
+                .frames
+                .get(i + 1 + repeated)
+                .is_some_and(|next| next.function == frame.function && next.line == frame.line)
+            {
+                repeated += 1;
+            }
+            if repeated > 0 {
+                diagnostic = diagnostic.with_note(format!(
+                    "    ... previous frame repeated {} more times",
+                    repeated
+                ));
+            }
+            i += 1 + repeated;
+        }
+        diagnostic
     }
 }
@@ -1,6 +1,9 @@
-use std::fmt::Display;
+use std::{cell::RefCell, fmt::Display, rc::Rc};
 
-use crate::{lox_callable::Callable, token::Token};
+use crate::{
+    lox_callable::Callable, lox_instance::LoxInstance, lox_map::LoxMap, stmt::FunctionStmt,
+    token::Token,
+};
 
 #[derive(Debug, Clone)]
 pub struct Expr {
@@ -20,6 +23,18 @@ pub enum ExprKind {
     Assgin(AssginExpr),
     Logical(LogicalExpr),
     Call(CallExpr),
+    Get(GetExpr),
+    Set(SetExpr),
+    This(ThisExpr),
+    Super(SuperExpr),
+    Interpolation(InterpolationExpr),
+    CompoundAssign(CompoundAssignExpr),
+    Update(UpdateExpr),
+    List(ListExpr),
+    Map(MapExpr),
+    Lambda(LambdaExpr),
+    Index(IndexExpr),
+    IndexSet(IndexSetExpr),
 }
 
 #[derive(Debug, Clone)]
@@ -59,7 +74,15 @@ pub enum LiteralValue {
     Boolean(bool),
     Nil,
     Callable(Callable),
+    Instance(Rc<RefCell<LoxInstance>>),
+    List(ListRef),
+    Map(MapRef),
 }
+
+/// Lists are shared and mutable: every copy of a list value sees the same elements.
+pub type ListRef = Rc<RefCell<Vec<LiteralValue>>>;
+/// Maps are shared the same way as lists.
+pub type MapRef = Rc<RefCell<LoxMap>>;
 #[derive(Debug, Clone)]
 pub struct LiteralExpr {
     pub value: LiteralValue,
@@ -72,6 +95,87 @@ pub struct CallExpr {
     pub arguments: Vec<Expr>,
 }
 
+#[derive(Debug, Clone)]
+pub struct GetExpr {
+    pub object: Box<Expr>,
+    pub name: Token,
+}
+
+#[derive(Debug, Clone)]
+pub struct SetExpr {
+    pub object: Box<Expr>,
+    pub name: Token,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThisExpr {
+    pub keyword: Token,
+}
+
+#[derive(Debug, Clone)]
+pub struct SuperExpr {
+    pub keyword: Token,
+    pub method: Token,
+}
+
+#[derive(Debug, Clone)]
+pub struct ListExpr {
+    pub elements: Vec<Expr>,
+}
+
+/// `fun (params) { body }` or `(params) => expr`. An arrow function's body is a single
+/// `return` of its expression, and its `name` is a `lambda` token at the `fun` or `=>`.
+#[derive(Debug, Clone)]
+pub struct LambdaExpr {
+    pub declaration: Rc<FunctionStmt>,
+}
+
+/// `{key: value, ...}`. `brace` is the `{`, used to report keys that can't be hashed.
+#[derive(Debug, Clone)]
+pub struct MapExpr {
+    pub brace: Token,
+    pub entries: Vec<(Expr, Expr)>,
+}
+
+/// `object[index]`. `bracket` is the `[`, kept for error locations.
+#[derive(Debug, Clone)]
+pub struct IndexExpr {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexSetExpr {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+    pub value: Box<Expr>,
+}
+
+/// `target op= value`, where `target` is a variable, a property or an index (`xs[i]`).
+#[derive(Debug, Clone)]
+pub struct CompoundAssignExpr {
+    pub target: Box<Expr>,
+    pub operator: Token,
+    pub value: Box<Expr>,
+}
+
+/// `++target`, `target++`, `--target` or `target--`.
+#[derive(Debug, Clone)]
+pub struct UpdateExpr {
+    pub target: Box<Expr>,
+    pub operator: Token,
+    pub prefix: bool,
+}
+
+/// A string with `${...}` holes: the literal fragments and the embedded expressions, in order.
+#[derive(Debug, Clone)]
+pub struct InterpolationExpr {
+    pub parts: Vec<Expr>,
+}
+
 impl Display for LiteralValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
@@ -79,7 +183,10 @@ impl Display for LiteralValue {
             LiteralValue::String(v) => write!(f, "\"{}\"", v),
             LiteralValue::Boolean(v) => write!(f, "{}", v),
             LiteralValue::Nil => write!(f, "Nil"),
-            LiteralValue::Callable(callable) => todo!(),
+            LiteralValue::Callable(callable) => write!(f, "{}", callable),
+            LiteralValue::Instance(instance) => write!(f, "{}", instance.borrow()),
+            LiteralValue::List(list) => write!(f, "<list of {}>", list.borrow().len()),
+            LiteralValue::Map(map) => write!(f, "<map of {}>", map.borrow().len()),
         }
     }
 }
@@ -123,9 +230,72 @@ impl Display for ExprKind {
                 exper.condition.kind, exper.left.kind, exper.right.kind
             ),
             ExprKind::Variable(expr) => write!(f, "(Variable {})", expr.name),
-            ExprKind::Assgin(assessment_expr) => todo!(),
-            ExprKind::Logical(logical_expr) => todo!(),
-            ExprKind::Call(call_expr) => todo!(),
+            ExprKind::Assgin(expr) => write!(f, "(assign {} {})", expr.name, expr.value.kind),
+            ExprKind::Logical(expr) => write!(
+                f,
+                "({} {} {})",
+                expr.operator, expr.left.kind, expr.right.kind
+            ),
+            ExprKind::Call(expr) => {
+                write!(f, "(call {}", expr.callee.kind)?;
+                for argument in &expr.arguments {
+                    write!(f, " {}", argument.kind)?;
+                }
+                write!(f, ")")
+            }
+            ExprKind::Get(expr) => write!(f, "(get {} {})", expr.object.kind, expr.name),
+            ExprKind::Set(expr) => write!(
+                f,
+                "(set {} {} {})",
+                expr.object.kind, expr.name, expr.value.kind
+            ),
+            ExprKind::This(_) => write!(f, "this"),
+            ExprKind::Super(expr) => write!(f, "(super {})", expr.method),
+            ExprKind::CompoundAssign(expr) => write!(
+                f,
+                "({} {} {})",
+                expr.operator, expr.target.kind, expr.value.kind
+            ),
+            ExprKind::Update(expr) if expr.prefix => {
+                write!(f, "(prefix {} {})", expr.operator, expr.target.kind)
+            }
+            ExprKind::Update(expr) => {
+                write!(f, "(postfix {} {})", expr.operator, expr.target.kind)
+            }
+            ExprKind::List(expr) => {
+                write!(f, "(list")?;
+                for element in &expr.elements {
+                    write!(f, " {}", element.kind)?;
+                }
+                write!(f, ")")
+            }
+            ExprKind::Lambda(expr) => {
+                write!(f, "(lambda")?;
+                for param in &expr.declaration.params {
+                    write!(f, " {}", param.lexeme)?;
+                }
+                write!(f, ")")
+            }
+            ExprKind::Map(expr) => {
+                write!(f, "(map")?;
+                for (key, value) in &expr.entries {
+                    write!(f, " {} {}", key.kind, value.kind)?;
+                }
+                write!(f, ")")
+            }
+            ExprKind::Index(expr) => write!(f, "(index {} {})", expr.object.kind, expr.index.kind),
+            ExprKind::IndexSet(expr) => write!(
+                f,
+                "(index-set {} {} {})",
+                expr.object.kind, expr.index.kind, expr.value.kind
+            ),
+            ExprKind::Interpolation(expr) => {
+                write!(f, "(interpolate")?;
+                for part in &expr.parts {
+                    write!(f, " {}", part.kind)?;
+                }
+                write!(f, ")")
+            }
         }
     }
 }
@@ -224,4 +394,114 @@ impl Expr {
             }),
         }
     }
+
+    pub fn get(id: usize, object: Expr, name: Token) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::Get(GetExpr {
+                object: Box::new(object),
+                name,
+            }),
+        }
+    }
+
+    pub fn set(id: usize, object: Expr, name: Token, value: Expr) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::Set(SetExpr {
+                object: Box::new(object),
+                name,
+                value: Box::new(value),
+            }),
+        }
+    }
+
+    pub fn this(id: usize, keyword: Token) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::This(ThisExpr { keyword }),
+        }
+    }
+
+    pub fn super_expr(id: usize, keyword: Token, method: Token) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::Super(SuperExpr { keyword, method }),
+        }
+    }
+
+    pub fn compound_assign(id: usize, target: Expr, operator: Token, value: Expr) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::CompoundAssign(CompoundAssignExpr {
+                target: Box::new(target),
+                operator,
+                value: Box::new(value),
+            }),
+        }
+    }
+
+    pub fn update(id: usize, target: Expr, operator: Token, prefix: bool) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::Update(UpdateExpr {
+                target: Box::new(target),
+                operator,
+                prefix,
+            }),
+        }
+    }
+
+    pub fn list(id: usize, elements: Vec<Expr>) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::List(ListExpr { elements }),
+        }
+    }
+
+    pub fn lambda(id: usize, declaration: FunctionStmt) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::Lambda(LambdaExpr {
+                declaration: Rc::new(declaration),
+            }),
+        }
+    }
+
+    pub fn map(id: usize, brace: Token, entries: Vec<(Expr, Expr)>) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::Map(MapExpr { brace, entries }),
+        }
+    }
+
+    pub fn index(id: usize, object: Expr, bracket: Token, index: Expr) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::Index(IndexExpr {
+                object: Box::new(object),
+                bracket,
+                index: Box::new(index),
+            }),
+        }
+    }
+
+    pub fn index_set(id: usize, object: Expr, bracket: Token, index: Expr, value: Expr) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::IndexSet(IndexSetExpr {
+                object: Box::new(object),
+                bracket,
+                index: Box::new(index),
+                value: Box::new(value),
+            }),
+        }
+    }
+
+    pub fn interpolation(id: usize, parts: Vec<Expr>) -> Self {
+        Expr {
+            id,
+            kind: ExprKind::Interpolation(InterpolationExpr { parts }),
+        }
+    }
 }
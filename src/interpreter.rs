@@ -7,16 +7,22 @@ use std::{
 };
 
 use crate::{
-    error::RunTimeError,
+    diagnostic::Diagnostics,
+    error::{CallFrame, RunTimeError},
     expr::{
-        AssginExpr, BinaryExpr, CallExpr, Expr, ExprKind, GroupingExpr, LiteralExpr, LiteralValue,
-        LogicalExpr, UnaryExpr, VariableExpr,
+        AssginExpr, BinaryExpr, CallExpr, CompoundAssignExpr, Expr, ExprKind, GetExpr,
+        GroupingExpr, IndexExpr, IndexSetExpr, InterpolationExpr, LambdaExpr, ListExpr, ListRef,
+        LiteralExpr, LiteralValue, LogicalExpr, MapExpr, SeparatorExpr, SetExpr, SuperExpr,
+        TernaryExpr, ThisExpr, UnaryExpr, UpdateExpr, VariableExpr,
     },
     lox_callable::{Callable, NativeFunction},
+    lox_class::LoxClass,
     lox_function::LoxFunction,
+    lox_instance::LoxInstance,
+    lox_map::{LoxMap, MapKey},
     stmt::{
-        BlockStmt, ControlFlow, ExpresstionStmt, FunctionStmt, IfStmt, PrintStmt, ReturnStmt, Stmt,
-        StmtExpr, StmtResult, VarStmt, WhileStmt,
+        BlockStmt, ClassStmt, ControlFlow, ExpresstionStmt, ForInStmt, FunctionStmt, IfStmt,
+        PrintStmt, ReturnStmt, Stmt, StmtExpr, StmtResult, VarStmt, WhileStmt,
     },
     token::{Token, TokenType},
     Environment::{Env, Environment},
@@ -24,21 +30,59 @@ use crate::{
 
 #[derive(Debug)]
 pub struct Interpreter {
-    pub had_error: bool,
+    pub diagnostics: Diagnostics,
     pub locals: HashMap<usize, usize>,
     pub globals: Env,
     pub environment: Env,
+    pub frames: Vec<CallFrame>,
+    pub max_call_depth: usize,
 }
 
 type InterpreterResult<T> = std::result::Result<T, RunTimeError>;
 
+/// The state of a running `for-in` loop.
+enum LoopIterator {
+    List {
+        list: ListRef,
+        index: usize,
+    },
+    Items(std::vec::IntoIter<LiteralValue>),
+    Range {
+        next: f64,
+        end: f64,
+    },
+    /// The object returned by `iterator()`.
+    Object(LiteralValue),
+}
+
+/// Every Lox call nests several Rust frames. `evaluate` and `execute` move onto a fresh
+/// heap-allocated stack segment when the current one runs low, so reaching this limit doesn't
+/// depend on the host thread's stack size; it bounds how much memory runaway recursion takes.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/// Once less than this much native stack is left, evaluation continues on a new segment.
+const STACK_RED_ZONE: usize = 128 * 1024;
+/// The size of each extra stack segment.
+const STACK_SEGMENT_SIZE: usize = 2 * 1024 * 1024;
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Interpreter {
+    /// The interpreter can run on any thread: deep Lox recursion grows the native stack on
+    /// demand, so the host only needs the stack its own frames use plus `STACK_RED_ZONE`
+    /// (128 KB). Scanning, parsing and resolving still recurse on the host's stack, by an amount
+    /// proportional to how deeply the source is nested rather than how deep calls go.
     pub fn new() -> Self {
         let globals = Rc::new(RefCell::new(Environment::new(None)));
         globals.borrow_mut().define(
             "clock".to_string(),
             Some(LiteralValue::Callable(Callable::Function(Rc::new(
                 NativeFunction::new(
+                    "clock",
                     |_, _| {
                         let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
                         return Ok(LiteralValue::Number(timestamp.as_millis() as f64));
@@ -49,12 +93,19 @@ impl Interpreter {
         );
 
         Self {
-            had_error: false,
+            diagnostics: Diagnostics::new(),
             locals: HashMap::new(),
             globals: Rc::clone(&globals),
             environment: Rc::clone(&globals),
+            frames: vec![],
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
         }
     }
+    /// Raising the limit needs no extra native stack from the host thread; each extra level of
+    /// recursion is paid for by the heap-allocated stack segments described on `new`.
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
     pub fn visit_litearal_expr(&self, expr: &LiteralExpr) -> Result<LiteralValue, RunTimeError> {
         Ok(expr.value.clone())
     }
@@ -72,6 +123,33 @@ impl Interpreter {
         }
         return self.evaluate(&expr.right);
     }
+    pub fn visit_separator_expr(
+        &mut self,
+        expr: &SeparatorExpr,
+    ) -> InterpreterResult<LiteralValue> {
+        self.evaluate(&expr.left)?;
+        self.evaluate(&expr.right)
+    }
+    pub fn visit_interpolation_expr(
+        &mut self,
+        expr: &InterpolationExpr,
+    ) -> InterpreterResult<LiteralValue> {
+        let mut text = String::new();
+        for part in &expr.parts {
+            let value = self.evaluate(part)?;
+            text += &self.stringify(value);
+        }
+        Ok(LiteralValue::String(text))
+    }
+    pub fn visit_ternary_expr(&mut self, expr: &TernaryExpr) -> InterpreterResult<LiteralValue> {
+        let condition = self.evaluate(&expr.condition)?;
+
+        if self.is_truthy(condition) {
+            self.evaluate(&expr.left)
+        } else {
+            self.evaluate(&expr.right)
+        }
+    }
     pub fn visit_grouping_expr(
         &mut self,
         expr: &GroupingExpr,
@@ -88,6 +166,17 @@ impl Interpreter {
                     "Expected a number".to_string(),
                 )),
             },
+            TokenType::TILDE => match right {
+                LiteralValue::Number(num) => {
+                    let num =
+                        self.to_integer(&expr.operator, num, "Operand must be an integer.")?;
+                    return Ok(LiteralValue::Number(!num as f64));
+                }
+                _ => Err(RunTimeError::new(
+                    expr.operator.clone(),
+                    "Operand must be an integer.".to_string(),
+                )),
+            },
             // if Nil = false & !Nil = true & !any = fasle
             TokenType::BANG => match right {
                 LiteralValue::Boolean(bol) => return Ok(LiteralValue::Boolean(!bol)),
@@ -104,13 +193,37 @@ impl Interpreter {
         let left = self.evaluate(&expr.left)?;
         let right = self.evaluate(&expr.right)?;
 
+        self.binary_operation(&expr.operator, left, right)
+    }
+    /// Applies a binary operator to two evaluated operands. Compound assignments share this
+    /// with `visit_binary_expr`.
+    fn binary_operation(
+        &self,
+        operator: &Token,
+        left: LiteralValue,
+        right: LiteralValue,
+    ) -> InterpreterResult<LiteralValue> {
+        match operator.token_type {
+            TokenType::PERCENT
+            | TokenType::STARSTAR
+            | TokenType::TILDESLASH
+            | TokenType::AMPERSAND
+            | TokenType::PIPE
+            | TokenType::CARET
+            | TokenType::LESSLESS
+            | TokenType::GREATERGREATER => {
+                return self.numeric_binary(operator, left, right);
+            }
+            _ => {}
+        }
+
         match (left, right) {
-            (LiteralValue::Number(l), LiteralValue::Number(r)) => match expr.operator.token_type {
+            (LiteralValue::Number(l), LiteralValue::Number(r)) => match operator.token_type {
                 TokenType::MINUS => return Ok(LiteralValue::Number(l - r)),
                 TokenType::SLASH => {
                     if r == 0.0 {
                         return Err(RunTimeError::new(
-                            expr.operator.clone(),
+                            operator.clone(),
                             "Can't divide by Zero".to_string(),
                         ));
                     }
@@ -127,51 +240,115 @@ impl Interpreter {
 
                 TokenType::EQUAL => todo!(),
                 _ => Err(RunTimeError::new(
-                    expr.operator.clone(),
+                    operator.clone(),
                     "Unexpected operator".to_string(),
                 )),
             },
-            (LiteralValue::String(l), LiteralValue::String(r)) => match expr.operator.token_type {
+            (LiteralValue::String(l), LiteralValue::String(r)) => match operator.token_type {
                 TokenType::PLUS => return Ok(LiteralValue::String(l + &r)),
                 TokenType::EQUALEQUAL => return Ok(LiteralValue::Boolean(l == r)),
                 TokenType::BANGEQUAL => return Ok(LiteralValue::Boolean(l != r)),
                 _ => Err(RunTimeError::new(
-                    expr.operator.clone(),
+                    operator.clone(),
                     "Unexpected operator".to_string(),
                 )),
             },
-            (LiteralValue::String(st), LiteralValue::Number(num)) => match expr.operator.token_type
-            {
+            (LiteralValue::String(st), LiteralValue::Number(num)) => match operator.token_type {
                 TokenType::PLUS => {
                     return Ok(LiteralValue::String(st.to_string() + &num.to_string()));
                 }
                 _ => Err(RunTimeError::new(
-                    expr.operator.clone(),
+                    operator.clone(),
                     "Unexpected operator".to_string(),
                 )),
             },
 
-            (LiteralValue::Number(num), LiteralValue::String(st)) => match expr.operator.token_type
-            {
+            (LiteralValue::Number(num), LiteralValue::String(st)) => match operator.token_type {
                 TokenType::PLUS => {
                     return Ok(LiteralValue::String(st.to_string() + &num.to_string()));
                 }
                 _ => Err(RunTimeError::new(
-                    expr.operator.clone(),
+                    operator.clone(),
                     "Unexpected operator".to_string(),
                 )),
             },
-            (l, r) => match expr.operator.token_type {
+            (l, r) => match operator.token_type {
                 TokenType::EQUALEQUAL => return Ok(LiteralValue::Boolean(self.is_equal(l, r))),
-                TokenType::BANGEQUAL => return Ok(LiteralValue::Boolean(self.is_equal(l, r))),
+                TokenType::BANGEQUAL => return Ok(LiteralValue::Boolean(!self.is_equal(l, r))),
                 _ => Err(RunTimeError::new(
-                    expr.operator.clone(),
+                    operator.clone(),
                     "Unexpected operator".to_string(),
                 )),
             },
         }
     }
 
+    /// The operators that only make sense on numbers, and on integer-valued numbers for the
+    /// bitwise ones.
+    fn numeric_binary(
+        &self,
+        operator: &Token,
+        left: LiteralValue,
+        right: LiteralValue,
+    ) -> InterpreterResult<LiteralValue> {
+        let (l, r) = match (left, right) {
+            (LiteralValue::Number(l), LiteralValue::Number(r)) => (l, r),
+            _ => {
+                return Err(RunTimeError::new(
+                    operator.clone(),
+                    "Operands must be numbers.".to_string(),
+                ));
+            }
+        };
+
+        let value = match operator.token_type {
+            TokenType::STARSTAR => l.powf(r),
+            TokenType::PERCENT | TokenType::TILDESLASH if r == 0.0 => {
+                return Err(RunTimeError::new(
+                    operator.clone(),
+                    "Can't divide by Zero".to_string(),
+                ));
+            }
+            TokenType::PERCENT => l - r * (l / r).floor(),
+            TokenType::TILDESLASH => (l / r).floor(),
+            _ => {
+                let message = "Operands must be integers.";
+                let l = self.to_integer(operator, l, message)?;
+                let r = self.to_integer(operator, r, message)?;
+                let value = match operator.token_type {
+                    TokenType::AMPERSAND => l & r,
+                    TokenType::PIPE => l | r,
+                    TokenType::CARET => l ^ r,
+                    _ => {
+                        if !(0..64).contains(&r) {
+                            return Err(RunTimeError::new(
+                                operator.clone(),
+                                "Shift amount must be between 0 and 63.".to_string(),
+                            ));
+                        }
+                        if operator.token_type == TokenType::LESSLESS {
+                            l << r
+                        } else {
+                            l >> r
+                        }
+                    }
+                };
+                value as f64
+            }
+        };
+        Ok(LiteralValue::Number(value))
+    }
+
+    /// Numbers are floats, so bitwise operators only accept ones that hold an exact integer.
+    fn to_integer(&self, operator: &Token, value: f64, message: &str) -> InterpreterResult<i64> {
+        // Beyond 2^53 a float can no longer tell neighbouring integers apart.
+        const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+        if value.fract() != 0.0 || value.abs() > MAX_SAFE_INTEGER {
+            return Err(RunTimeError::new(operator.clone(), message.to_string()));
+        }
+        Ok(value as i64)
+    }
+
     pub fn visist_call_expr(&mut self, expr: &CallExpr) -> InterpreterResult<LiteralValue> {
         let callee = self.evaluate(&expr.callee)?;
 
@@ -181,31 +358,278 @@ impl Interpreter {
             arguments.push(self.evaluate(argument)?);
         }
 
+        self.call_value(callee, arguments, &expr.paren)
+    }
+
+    /// Calls `callee`, reporting errors at `paren` and recording the call on the stack trace.
+    fn call_value(
+        &mut self,
+        callee: LiteralValue,
+        arguments: Vec<LiteralValue>,
+        paren: &Token,
+    ) -> InterpreterResult<LiteralValue> {
         match callee {
-            LiteralValue::Callable(callable) => match callable {
-                Callable::Function(function) => {
-                    if arguments.len() != function.arity() {
-                        return Err(RunTimeError::new(
-                            expr.paren.clone(),
-                            "Expected ".to_string()
-                                + &function.arity().to_string()
-                                + " arguments but got "
-                                + &arguments.len().to_string()
-                                + ".",
-                        ));
-                    }
-                    return function.call(self, &arguments);
+            LiteralValue::Callable(callable) => {
+                if arguments.len() != callable.arity() {
+                    return Err(RunTimeError::new(
+                        paren.clone(),
+                        "Expected ".to_string()
+                            + &callable.arity().to_string()
+                            + " arguments but got "
+                            + &arguments.len().to_string()
+                            + ".",
+                    ));
                 }
-            },
+                if self.frames.len() >= self.max_call_depth {
+                    return Err(RunTimeError::new(
+                        paren.clone(),
+                        "Stack overflow.".to_string(),
+                    ));
+                }
+                self.frames.push(CallFrame {
+                    function: callable.name(),
+                    line: paren.line,
+                });
+                let result = callable.call(self, &arguments).map_err(|mut error| {
+                    // Only the innermost failing call sees an empty trace, so the stack is captured once.
+                    if error.trace.frames.is_empty() {
+                        error.trace.frames = self.frames.iter().rev().cloned().collect();
+                    }
+                    error
+                });
+                self.frames.pop();
+                return result;
+            }
             _ => {
                 return Err(RunTimeError::new(
-                    expr.paren.clone(),
+                    paren.clone(),
                     "Can only call functions and classes.".to_string(),
                 ));
             }
         }
     }
 
+    pub fn visit_get_expr(&mut self, expr: &GetExpr) -> InterpreterResult<LiteralValue> {
+        match self.evaluate(&expr.object)? {
+            LiteralValue::Instance(instance) => LoxInstance::get(&instance, &expr.name),
+            _ => Err(RunTimeError::new(
+                expr.name.clone(),
+                "Only instances have properties.".to_string(),
+            )),
+        }
+    }
+
+    pub fn visit_set_expr(&mut self, expr: &SetExpr) -> InterpreterResult<LiteralValue> {
+        match self.evaluate(&expr.object)? {
+            LiteralValue::Instance(instance) => {
+                let value = self.evaluate(&expr.value)?;
+                instance.borrow_mut().set(&expr.name, value.clone());
+                Ok(value)
+            }
+            _ => Err(RunTimeError::new(
+                expr.name.clone(),
+                "Only instances have fields.".to_string(),
+            )),
+        }
+    }
+
+    pub fn visit_list_expr(&mut self, expr: &ListExpr) -> InterpreterResult<LiteralValue> {
+        let mut elements = vec![];
+        for element in &expr.elements {
+            elements.push(self.evaluate(element)?);
+        }
+        Ok(LiteralValue::List(Rc::new(RefCell::new(elements))))
+    }
+
+    pub fn visit_lambda_expr(&mut self, expr: &LambdaExpr) -> InterpreterResult<LiteralValue> {
+        Ok(LiteralValue::Callable(Callable::Function(Rc::new(
+            LoxFunction::new(
+                Rc::clone(&expr.declaration),
+                Rc::clone(&self.environment),
+                false,
+            ),
+        ))))
+    }
+
+    pub fn visit_map_expr(&mut self, expr: &MapExpr) -> InterpreterResult<LiteralValue> {
+        let mut map = LoxMap::new();
+        for (key, value) in &expr.entries {
+            let key = self.evaluate(key)?;
+            let key = self.map_key(&expr.brace, &key)?;
+            let value = self.evaluate(value)?;
+            map.insert(key, value);
+        }
+        Ok(LiteralValue::Map(Rc::new(RefCell::new(map))))
+    }
+
+    pub fn visit_index_expr(&mut self, expr: &IndexExpr) -> InterpreterResult<LiteralValue> {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+        self.index_get(&expr.bracket, &object, &index)
+    }
+
+    pub fn visit_index_set_expr(&mut self, expr: &IndexSetExpr) -> InterpreterResult<LiteralValue> {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+        // Check the list index before evaluating the value, as a property set checks the object.
+        if let LiteralValue::List(list) = &object {
+            self.list_index(&expr.bracket, list, &index)?;
+        }
+        let value = self.evaluate(&expr.value)?;
+        self.index_set(&expr.bracket, &object, &index, value.clone())?;
+        Ok(value)
+    }
+
+    /// `object[index]` for a list element or a map entry. A missing map key is an error, like
+    /// an undefined property.
+    fn index_get(
+        &self,
+        bracket: &Token,
+        object: &LiteralValue,
+        index: &LiteralValue,
+    ) -> InterpreterResult<LiteralValue> {
+        match object {
+            LiteralValue::List(list) => {
+                let index = self.list_index(bracket, list, index)?;
+                Ok(list.borrow()[index].clone())
+            }
+            LiteralValue::Map(map) => {
+                let key = self.map_key(bracket, index)?;
+                match map.borrow().get(&key) {
+                    Some(value) => Ok(value.clone()),
+                    None => Err(RunTimeError::new(
+                        bracket.clone(),
+                        format!("Undefined key {}.", self.stringify_element(index.clone())),
+                    )),
+                }
+            }
+            _ => Err(RunTimeError::new(
+                bracket.clone(),
+                "Only lists and maps can be indexed.".to_string(),
+            )),
+        }
+    }
+
+    /// `object[index] = value`. Assigning to a missing map key adds it.
+    fn index_set(
+        &self,
+        bracket: &Token,
+        object: &LiteralValue,
+        index: &LiteralValue,
+        value: LiteralValue,
+    ) -> InterpreterResult<()> {
+        match object {
+            LiteralValue::List(list) => {
+                let index = self.list_index(bracket, list, index)?;
+                list.borrow_mut()[index] = value;
+                Ok(())
+            }
+            LiteralValue::Map(map) => {
+                let key = self.map_key(bracket, index)?;
+                map.borrow_mut().insert(key, value);
+                Ok(())
+            }
+            _ => Err(RunTimeError::new(
+                bracket.clone(),
+                "Only lists and maps can be indexed.".to_string(),
+            )),
+        }
+    }
+
+    fn map_key(&self, token: &Token, key: &LiteralValue) -> InterpreterResult<MapKey> {
+        MapKey::from_value(key).ok_or_else(|| {
+            let message = match key {
+                LiteralValue::Number(_) => "Map keys can't be NaN.",
+                _ => "Map keys must be strings, numbers, booleans or nil.",
+            };
+            RunTimeError::new(token.clone(), message.to_string())
+        })
+    }
+
+    /// Checks that `index` names an existing element of `list` and returns it as a `usize`.
+    fn list_index(
+        &self,
+        bracket: &Token,
+        list: &ListRef,
+        index: &LiteralValue,
+    ) -> InterpreterResult<usize> {
+        let LiteralValue::Number(index) = *index else {
+            return Err(RunTimeError::new(
+                bracket.clone(),
+                "List index must be an integer.".to_string(),
+            ));
+        };
+        let index = self.to_integer(bracket, index, "List index must be an integer.")?;
+        let len = list.borrow().len();
+        if index < 0 || index as usize >= len {
+            return Err(RunTimeError::new(
+                bracket.clone(),
+                format!("List index {} out of range for length {}.", index, len),
+            ));
+        }
+        Ok(index as usize)
+    }
+
+    pub fn visit_this_expr(
+        &mut self,
+        id: usize,
+        expr: &ThisExpr,
+    ) -> InterpreterResult<LiteralValue> {
+        match self.look_up_variable(id, &expr.keyword)? {
+            Some(value) => Ok(value),
+            None => Err(RunTimeError::new(
+                expr.keyword.clone(),
+                "Can't use 'this' outside of a class.".to_string(),
+            )),
+        }
+    }
+
+    pub fn visit_super_expr(
+        &mut self,
+        id: usize,
+        expr: &SuperExpr,
+    ) -> InterpreterResult<LiteralValue> {
+        let distance = match self.locals.get(&id) {
+            Some(distance) => *distance,
+            None => {
+                return Err(RunTimeError::new(
+                    expr.keyword.clone(),
+                    "Can't use 'super' outside of a class.".to_string(),
+                ));
+            }
+        };
+
+        let superclass = match self.get_at(distance, "super") {
+            Some(LiteralValue::Callable(Callable::Class(class))) => class,
+            _ => {
+                return Err(RunTimeError::new(
+                    expr.keyword.clone(),
+                    "Superclass must be a class.".to_string(),
+                ));
+            }
+        };
+        // "this" always lives in the scope right inside the one holding "super".
+        let object = match self.get_at(distance - 1, "this") {
+            Some(LiteralValue::Instance(instance)) => instance,
+            _ => {
+                return Err(RunTimeError::new(
+                    expr.keyword.clone(),
+                    "Can't use 'super' outside of a method.".to_string(),
+                ));
+            }
+        };
+
+        match superclass.find_method(&expr.method.lexeme) {
+            Some(method) => Ok(LiteralValue::Callable(Callable::Function(Rc::new(
+                method.bind(object),
+            )))),
+            None => Err(RunTimeError::new(
+                expr.method.clone(),
+                "Undefined property '".to_string() + &expr.method.lexeme + "'.",
+            )),
+        }
+    }
+
     pub fn visit_variable_expr(
         &mut self,
         id: usize,
@@ -214,10 +638,10 @@ impl Interpreter {
         if let Some(value) = self.look_up_variable(id, &expr.name)? {
             return Ok(value);
         } else {
-            Err(RunTimeError {
-                token: expr.name.clone(),
-                message: "".to_string(),
-            })
+            Err(RunTimeError::new(
+                expr.name.clone(),
+                "Undefined variable '".to_string() + &expr.name.lexeme + "'.",
+            ))
         }
     }
     pub fn look_up_variable(
@@ -242,6 +666,7 @@ impl Interpreter {
         let function = LiteralValue::Callable(Callable::Function(Rc::new(LoxFunction::new(
             Rc::new(stmt.clone()),
             Rc::clone(&self.environment),
+            false,
         ))));
         self.environment
             .borrow_mut()
@@ -249,6 +674,60 @@ impl Interpreter {
         Ok(None)
     }
 
+    pub fn visit_class_stmt(&mut self, stmt: &ClassStmt) -> StmtResult {
+        let mut superclass = None;
+        if let Some(superclass_expr) = &stmt.superclass {
+            match self.evaluate(superclass_expr)? {
+                LiteralValue::Callable(Callable::Class(class)) => superclass = Some(class),
+                _ => {
+                    let name = match &superclass_expr.kind {
+                        ExprKind::Variable(variable) => variable.name.clone(),
+                        _ => stmt.name.clone(),
+                    };
+                    return Err(RunTimeError::new(
+                        name,
+                        "Superclass must be a class.".to_string(),
+                    ));
+                }
+            }
+        }
+
+        self.environment
+            .borrow_mut()
+            .define(stmt.name.lexeme.clone(), None);
+
+        let enclosing = Rc::clone(&self.environment);
+        if let Some(superclass) = &superclass {
+            self.environment = Rc::new(RefCell::new(Environment::new(Some(Rc::clone(
+                &self.environment,
+            )))));
+            self.environment.borrow_mut().define(
+                "super".to_string(),
+                Some(LiteralValue::Callable(Callable::Class(Rc::clone(
+                    superclass,
+                )))),
+            );
+        }
+
+        let mut methods = HashMap::new();
+        for method in &stmt.methods {
+            let function = LoxFunction::new(
+                Rc::new(method.clone()),
+                Rc::clone(&self.environment),
+                method.name.lexeme == "init",
+            );
+            methods.insert(method.name.lexeme.clone(), Rc::new(function));
+        }
+
+        let class = LoxClass::new(stmt.name.lexeme.clone(), superclass, methods);
+        self.environment = enclosing;
+        self.environment.borrow_mut().assign(
+            &stmt.name,
+            LiteralValue::Callable(Callable::Class(Rc::new(class))),
+        )?;
+        Ok(None)
+    }
+
     pub fn visit_if_stmt(&mut self, stmt: &IfStmt) -> StmtResult {
         let is_true = self.evaluate(&stmt.condition)?;
         if self.is_truthy(is_true) {
@@ -265,17 +744,15 @@ impl Interpreter {
             LiteralValue::String(_) | LiteralValue::Number(_) => return true,
             LiteralValue::Boolean(bol) => return bol,
             LiteralValue::Nil => return false,
-            LiteralValue::Callable(_) => todo!(),
+            LiteralValue::Callable(_) | LiteralValue::Instance(_) => return true,
+            // Like instances, lists and maps are truthy even when empty.
+            LiteralValue::List(_) | LiteralValue::Map(_) => return true,
         }
     }
 
     pub fn visit_print_stmt(&mut self, stmt: &PrintStmt) -> StmtResult {
-        match self.evaluate(&stmt.expr) {
-            Ok(value) => println!("{}", self.stringify(value)),
-            Err(_) => {
-                self.had_error = true;
-            }
-        }
+        let value = self.evaluate(&stmt.expr)?;
+        println!("{}", self.stringify(value));
         Ok(None)
     }
 
@@ -289,16 +766,12 @@ impl Interpreter {
 
     pub fn visit_var_stmt(&mut self, stmt: &VarStmt) -> StmtResult {
         match &stmt.initializer {
-            Some(expr) => match self.evaluate(expr) {
-                Ok(val) => {
-                    self.environment
-                        .borrow_mut()
-                        .define(stmt.name.lexeme.clone(), Some(val));
-                }
-                Err(_) => {
-                    self.had_error = true;
-                }
-            },
+            Some(expr) => {
+                let val = self.evaluate(expr)?;
+                self.environment
+                    .borrow_mut()
+                    .define(stmt.name.lexeme.clone(), Some(val));
+            }
             None => {
                 self.environment
                     .borrow_mut()
@@ -317,60 +790,386 @@ impl Interpreter {
                             return Ok(Some(ControlFlow::Return(literal_value)));
                         }
                         ControlFlow::Break => return Ok(None),
-                        ControlFlow::Continue => return Ok(None),
+                        ControlFlow::Continue => {}
                     }
                 }
+                if let Some(increment) = &stmt.increment {
+                    self.evaluate(increment)?;
+                }
             } else {
                 break;
             }
         }
         Ok(None)
     }
+    pub fn visit_for_in_stmt(&mut self, stmt: &ForInStmt) -> StmtResult {
+        let iterable = self.evaluate(&stmt.iterable)?;
+        let mut iterator = match &stmt.range_end {
+            Some(range_end) => match (iterable, self.evaluate(range_end)?) {
+                (LiteralValue::Number(next), LiteralValue::Number(end)) => {
+                    LoopIterator::Range { next, end }
+                }
+                _ => {
+                    return Err(RunTimeError::new(
+                        stmt.keyword.clone(),
+                        "Range bounds must be numbers.".to_string(),
+                    ));
+                }
+            },
+            None => self.loop_iterator(&stmt.keyword, iterable)?,
+        };
+
+        while let Some(item) = self.next_item(&stmt.keyword, &mut iterator)? {
+            // Each iteration gets its own variable, so closures capture the current item.
+            let environment = Rc::new(RefCell::new(Environment::new(Some(
+                self.environment.clone(),
+            ))));
+            environment
+                .borrow_mut()
+                .define(stmt.name.lexeme.clone(), Some(item));
+            let previous = std::mem::replace(&mut self.environment, environment);
+            let flow = self.execute(&stmt.body);
+            self.environment = previous;
+
+            match flow? {
+                Some(ControlFlow::Return(value)) => return Ok(Some(ControlFlow::Return(value))),
+                Some(ControlFlow::Break) => break,
+                Some(ControlFlow::Continue) | None => {}
+            }
+        }
+        Ok(None)
+    }
+    /// Lists are walked by index, so elements pushed during the loop are visited too. Maps
+    /// yield a snapshot of their keys and strings yield their characters. Any other value
+    /// must be an instance with an `iterator()` method.
+    fn loop_iterator(
+        &mut self,
+        keyword: &Token,
+        iterable: LiteralValue,
+    ) -> InterpreterResult<LoopIterator> {
+        match iterable {
+            LiteralValue::List(list) => Ok(LoopIterator::List { list, index: 0 }),
+            LiteralValue::Map(map) => {
+                let keys: Vec<LiteralValue> = map
+                    .borrow()
+                    .entries()
+                    .iter()
+                    .map(|(key, _)| key.to_value())
+                    .collect();
+                Ok(LoopIterator::Items(keys.into_iter()))
+            }
+            LiteralValue::String(str) => {
+                let chars: Vec<LiteralValue> = str
+                    .chars()
+                    .map(|char| LiteralValue::String(char.to_string()))
+                    .collect();
+                Ok(LoopIterator::Items(chars.into_iter()))
+            }
+            object => match self.find_method(keyword, &object, "iterator") {
+                Some(method) => Ok(LoopIterator::Object(self.call_value(method, vec![], keyword)?)),
+                None => Err(RunTimeError::new(
+                    keyword.clone(),
+                    "Can only iterate over lists, maps, strings, ranges and objects with an 'iterator' method."
+                        .to_string(),
+                )),
+            },
+        }
+    }
+    /// Returns `None` once the iterator is exhausted. An object iterator is done when its
+    /// `next()` returns nil.
+    fn next_item(
+        &mut self,
+        keyword: &Token,
+        iterator: &mut LoopIterator,
+    ) -> InterpreterResult<Option<LiteralValue>> {
+        match iterator {
+            LoopIterator::List { list, index } => {
+                let item = list.borrow().get(*index).cloned();
+                *index += 1;
+                Ok(item)
+            }
+            LoopIterator::Items(items) => Ok(items.next()),
+            LoopIterator::Range { next, end } => {
+                if *next >= *end {
+                    return Ok(None);
+                }
+                let item = LiteralValue::Number(*next);
+                *next += 1.0;
+                Ok(Some(item))
+            }
+            LoopIterator::Object(object) => {
+                let Some(method) = self.find_method(keyword, object, "next") else {
+                    return Err(RunTimeError::new(
+                        keyword.clone(),
+                        "Iterator must have a 'next' method.".to_string(),
+                    ));
+                };
+                match self.call_value(method, vec![], keyword)? {
+                    LiteralValue::Nil => Ok(None),
+                    item => Ok(Some(item)),
+                }
+            }
+        }
+    }
+    /// Looks up a method or callable field on an instance, for the iteration protocol.
+    fn find_method(
+        &self,
+        keyword: &Token,
+        object: &LiteralValue,
+        name: &str,
+    ) -> Option<LiteralValue> {
+        let LiteralValue::Instance(instance) = object else {
+            return None;
+        };
+        let mut name_token = keyword.clone();
+        name_token.lexeme = name.to_string();
+        LoxInstance::get(instance, &name_token).ok()
+    }
     pub fn visit_break_stmt(&self) -> StmtResult {
         return Ok(Some(ControlFlow::Break));
     }
+    pub fn visit_continue_stmt(&self) -> StmtResult {
+        return Ok(Some(ControlFlow::Continue));
+    }
     pub fn visit_assign_expr(
         &mut self,
         id: usize,
         expr: &AssginExpr,
     ) -> InterpreterResult<LiteralValue> {
         let value = self.evaluate(&expr.value)?;
-
+        self.assign_variable(id, &expr.name, value.clone())?;
+        Ok(value)
+    }
+    fn assign_variable(&mut self, id: usize, name: &Token, value: LiteralValue) -> StmtResult {
         if let Some(distance) = self.locals.get(&id) {
-            self.assign_at(distance.clone(), &expr.name, value.clone());
+            self.assign_at(distance.clone(), name, value);
         } else {
-            self.globals
-                .borrow_mut()
-                .assign(&expr.name, value.clone())?;
+            self.globals.borrow_mut().assign(name, value)?;
+        }
+        Ok(None)
+    }
+    pub fn visit_compound_assign_expr(
+        &mut self,
+        expr: &CompoundAssignExpr,
+    ) -> InterpreterResult<LiteralValue> {
+        // `a += b` applies `+` but errors still point at the `+=` the user wrote.
+        let mut operator = expr.operator.clone();
+        operator.token_type = match expr.operator.token_type {
+            TokenType::PLUSEQUAL => TokenType::PLUS,
+            TokenType::MINUSEQUAL => TokenType::MINUS,
+            TokenType::STAREQUAL => TokenType::STAR,
+            TokenType::SLASHEQUAL => TokenType::SLASH,
+            _ => TokenType::PERCENT,
+        };
+        self.update_target(&expr.target, |interpreter, current| {
+            let value = interpreter.evaluate(&expr.value)?;
+            let value = interpreter.binary_operation(&operator, current, value)?;
+            Ok((value.clone(), value))
+        })
+    }
+    pub fn visit_update_expr(&mut self, expr: &UpdateExpr) -> InterpreterResult<LiteralValue> {
+        self.update_target(&expr.target, |_, current| {
+            let LiteralValue::Number(current) = current else {
+                return Err(RunTimeError::new(
+                    expr.operator.clone(),
+                    format!("Operand of '{}' must be a number.", expr.operator.lexeme),
+                ));
+            };
+            let updated = match expr.operator.token_type {
+                TokenType::PLUSPLUS => current + 1.0,
+                _ => current - 1.0,
+            };
+            let result = if expr.prefix { updated } else { current };
+            Ok((LiteralValue::Number(updated), LiteralValue::Number(result)))
+        })
+    }
+    /// Reads a variable, property or list element, lets `update` compute the value to store and the value
+    /// of the whole expression, then writes back. The target's object is evaluated only once.
+    fn update_target(
+        &mut self,
+        target: &Expr,
+        update: impl FnOnce(&mut Self, LiteralValue) -> InterpreterResult<(LiteralValue, LiteralValue)>,
+    ) -> InterpreterResult<LiteralValue> {
+        match &target.kind {
+            ExprKind::Variable(variable) => {
+                let current = self.visit_variable_expr(target.id, variable)?;
+                let (stored, result) = update(self, current)?;
+                self.assign_variable(target.id, &variable.name, stored)?;
+                Ok(result)
+            }
+            ExprKind::Get(get) => match self.evaluate(&get.object)? {
+                LiteralValue::Instance(instance) => {
+                    let current = LoxInstance::get(&instance, &get.name)?;
+                    let (stored, result) = update(self, current)?;
+                    instance.borrow_mut().set(&get.name, stored);
+                    Ok(result)
+                }
+                _ => Err(RunTimeError::new(
+                    get.name.clone(),
+                    "Only instances have fields.".to_string(),
+                )),
+            },
+            ExprKind::Index(index_expr) => {
+                let object = self.evaluate(&index_expr.object)?;
+                let index = self.evaluate(&index_expr.index)?;
+                let current = self.index_get(&index_expr.bracket, &object, &index)?;
+                let (stored, result) = update(self, current)?;
+                self.index_set(&index_expr.bracket, &object, &index, stored)?;
+                Ok(result)
+            }
+            _ => {
+                unreachable!("the parser only builds updates of variables, properties and indexes")
+            }
         }
-        Ok(value)
     }
 
     fn is_equal(&self, l: LiteralValue, r: LiteralValue) -> bool {
+        self.values_equal(l, r, &mut vec![])
+    }
+    /// Lists compare element by element and maps compare their entries regardless of order.
+    /// `comparing` holds the addresses of the collections already being compared further up,
+    /// so cyclic values don't recurse forever.
+    fn values_equal(
+        &self,
+        l: LiteralValue,
+        r: LiteralValue,
+        comparing: &mut Vec<(*const (), *const ())>,
+    ) -> bool {
         match (l, r) {
+            (LiteralValue::List(l), LiteralValue::List(r)) => {
+                let pair = (Rc::as_ptr(&l) as *const (), Rc::as_ptr(&r) as *const ());
+                if pair.0 == pair.1 || comparing.contains(&pair) {
+                    return true;
+                }
+                let (left, right) = (l.borrow().clone(), r.borrow().clone());
+                if left.len() != right.len() {
+                    return false;
+                }
+                comparing.push(pair);
+                let equal = left
+                    .into_iter()
+                    .zip(right)
+                    .all(|(l, r)| self.values_equal(l, r, comparing));
+                comparing.pop();
+                equal
+            }
+            (LiteralValue::Map(l), LiteralValue::Map(r)) => {
+                let pair = (Rc::as_ptr(&l) as *const (), Rc::as_ptr(&r) as *const ());
+                if pair.0 == pair.1 || comparing.contains(&pair) {
+                    return true;
+                }
+                let (left, right) = (l.borrow().clone(), r.borrow().clone());
+                if left.len() != right.len() {
+                    return false;
+                }
+                comparing.push(pair);
+                let equal = left.entries().iter().all(|(key, value)| {
+                    right.get(key).is_some_and(|other| {
+                        self.values_equal(value.clone(), other.clone(), comparing)
+                    })
+                });
+                comparing.pop();
+                equal
+            }
             (LiteralValue::Number(l), LiteralValue::Number(r)) => l == r,
             (LiteralValue::String(l), LiteralValue::String(r)) => l == r,
             (LiteralValue::Boolean(l), LiteralValue::Boolean(r)) => l == r,
             (LiteralValue::Nil, LiteralValue::Nil) => true,
+            (LiteralValue::Instance(l), LiteralValue::Instance(r)) => Rc::ptr_eq(&l, &r),
+            (
+                LiteralValue::Callable(Callable::Class(l)),
+                LiteralValue::Callable(Callable::Class(r)),
+            ) => Rc::ptr_eq(&l, &r),
             _ => false,
         }
     }
     pub fn evaluate(&mut self, expr: &Expr) -> Result<LiteralValue, RunTimeError> {
-        match &expr.kind {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || match &expr.kind {
             ExprKind::Binary(binary_expr) => self.visit_binary_expr(binary_expr),
             ExprKind::Grouping(grouping_expr) => self.visit_grouping_expr(grouping_expr),
             ExprKind::Literal(literal_expr) => self.visit_litearal_expr(literal_expr),
             ExprKind::Unary(unary_expr) => self.visit_unary_expr(unary_expr),
-            ExprKind::Separator(_) => todo!(),
-            ExprKind::Ternary(_) => todo!(),
+            ExprKind::Separator(separator_expr) => self.visit_separator_expr(separator_expr),
+            ExprKind::Ternary(ternary_expr) => self.visit_ternary_expr(ternary_expr),
             ExprKind::Variable(var_expr) => self.visit_variable_expr(expr.id, var_expr),
             ExprKind::Assgin(assessment_expr) => self.visit_assign_expr(expr.id, assessment_expr),
             ExprKind::Logical(logical_expr) => self.visit_logical_exper(logical_expr),
             ExprKind::Call(call_expr) => self.visist_call_expr(call_expr),
-        }
+            ExprKind::Get(get_expr) => self.visit_get_expr(get_expr),
+            ExprKind::Set(set_expr) => self.visit_set_expr(set_expr),
+            ExprKind::This(this_expr) => self.visit_this_expr(expr.id, this_expr),
+            ExprKind::Super(super_expr) => self.visit_super_expr(expr.id, super_expr),
+            ExprKind::CompoundAssign(compound_expr) => {
+                self.visit_compound_assign_expr(compound_expr)
+            }
+            ExprKind::Update(update_expr) => self.visit_update_expr(update_expr),
+            ExprKind::Interpolation(interpolation_expr) => {
+                self.visit_interpolation_expr(interpolation_expr)
+            }
+            ExprKind::List(list_expr) => self.visit_list_expr(list_expr),
+            ExprKind::Map(map_expr) => self.visit_map_expr(map_expr),
+            ExprKind::Lambda(lambda_expr) => self.visit_lambda_expr(lambda_expr),
+            ExprKind::Index(index_expr) => self.visit_index_expr(index_expr),
+            ExprKind::IndexSet(index_set_expr) => self.visit_index_set_expr(index_set_expr),
+        })
     }
     fn stringify(&self, value: LiteralValue) -> String {
+        self.stringify_nested(value, &mut vec![])
+    }
+    /// Strings inside collections and error messages are quoted, so `["a, b"]` and
+    /// `["a", "b"]` print differently.
+    fn stringify_element(&self, value: LiteralValue) -> String {
+        self.stringify_element_nested(value, &mut vec![])
+    }
+    fn stringify_element_nested(
+        &self,
+        value: LiteralValue,
+        enclosing: &mut Vec<*const ()>,
+    ) -> String {
+        match value {
+            LiteralValue::String(str) => format!("{:?}", str),
+            _ => self.stringify_nested(value, enclosing),
+        }
+    }
+    /// `enclosing` holds the addresses of the collections currently being printed; a list or
+    /// map that contains itself prints as `[...]` or `{...}` at the point it repeats.
+    fn stringify_nested(&self, value: LiteralValue, enclosing: &mut Vec<*const ()>) -> String {
         match value {
+            LiteralValue::List(list) => {
+                let address = Rc::as_ptr(&list) as *const ();
+                if enclosing.contains(&address) {
+                    return "[...]".to_string();
+                }
+                enclosing.push(address);
+                let elements: Vec<String> = list
+                    .borrow()
+                    .iter()
+                    .map(|element| self.stringify_element_nested(element.clone(), enclosing))
+                    .collect();
+                enclosing.pop();
+                format!("[{}]", elements.join(", "))
+            }
+            LiteralValue::Map(map) => {
+                let address = Rc::as_ptr(&map) as *const ();
+                if enclosing.contains(&address) {
+                    return "{...}".to_string();
+                }
+                enclosing.push(address);
+                let entries: Vec<String> = map
+                    .borrow()
+                    .entries()
+                    .iter()
+                    .map(|(key, value)| {
+                        format!(
+                            "{}: {}",
+                            self.stringify_element_nested(key.to_value(), enclosing),
+                            self.stringify_element_nested(value.clone(), enclosing)
+                        )
+                    })
+                    .collect();
+                enclosing.pop();
+                format!("{{{}}}", entries.join(", "))
+            }
             LiteralValue::String(str) => str,
             LiteralValue::Number(num) => {
                 let num_str = num.to_string();
@@ -381,29 +1180,39 @@ impl Interpreter {
             }
             LiteralValue::Boolean(bol) => bol.to_string(),
             LiteralValue::Nil => "Nil".to_string(),
-            LiteralValue::Callable(_) => todo!(),
+            LiteralValue::Callable(callable) => callable.to_string(),
+            LiteralValue::Instance(instance) => instance.borrow().to_string(),
         }
     }
     pub fn interpret(&mut self, statements: Vec<Stmt>) {
-        statements.iter().for_each(|stmt| match self.execute(stmt) {
-            Ok(_) => {}
-            Err(_) => self.had_error = true,
-        });
+        for stmt in statements.iter() {
+            if let Err(error) = self.execute(stmt) {
+                self.diagnostics.push(error.to_diagnostic());
+                return;
+            }
+        }
     }
     pub fn execute(&mut self, statement: &Stmt) -> StmtResult {
-        match &statement.expresstion {
-            StmtExpr::Print(stmt) => return self.visit_print_stmt(stmt),
-            StmtExpr::Expresstion(stmt) => return self.visit_expresstion_stmt(stmt),
-            StmtExpr::Var(stmt) => return self.visit_var_stmt(stmt),
-            StmtExpr::Block(block) => return self.visit_block_stmt(block),
-            StmtExpr::If(stmt) => {
-                return self.visit_if_stmt(stmt);
-            }
-            StmtExpr::While(stmt) => return self.visit_while_stmt(&stmt),
-            StmtExpr::Break => return self.visit_break_stmt(),
-            StmtExpr::Function(function_stmt) => return self.visit_function_stmt(function_stmt),
-            StmtExpr::Return(return_stmt) => self.visit_retunr_stmt(return_stmt),
-        }
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+            match &statement.expresstion {
+                StmtExpr::Print(stmt) => return self.visit_print_stmt(stmt),
+                StmtExpr::Expresstion(stmt) => return self.visit_expresstion_stmt(stmt),
+                StmtExpr::Var(stmt) => return self.visit_var_stmt(stmt),
+                StmtExpr::Block(block) => return self.visit_block_stmt(block),
+                StmtExpr::If(stmt) => {
+                    return self.visit_if_stmt(stmt);
+                }
+                StmtExpr::While(stmt) => return self.visit_while_stmt(&stmt),
+                StmtExpr::ForIn(stmt) => self.visit_for_in_stmt(stmt),
+                StmtExpr::Break(_) => return self.visit_break_stmt(),
+                StmtExpr::Continue(_) => return self.visit_continue_stmt(),
+                StmtExpr::Function(function_stmt) => {
+                    return self.visit_function_stmt(function_stmt)
+                }
+                StmtExpr::Return(return_stmt) => self.visit_retunr_stmt(return_stmt),
+                StmtExpr::Class(class_stmt) => self.visit_class_stmt(class_stmt),
+            }
+        })
     }
     pub fn visit_block_stmt(&mut self, block: &BlockStmt) -> StmtResult {
         return self.exeucute_block(
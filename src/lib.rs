@@ -1,12 +1,16 @@
 mod Environment;
+pub mod diagnostic;
 mod error;
-mod expr;
-mod interpreter;
-mod lox;
+pub mod expr;
+pub mod interpreter;
+pub mod lox;
 mod lox_callable;
+mod lox_class;
 mod lox_function;
+mod lox_instance;
+mod lox_map;
 pub mod parser;
 mod resolver;
-mod scanner;
-mod stmt;
-mod token;
+pub mod scanner;
+pub mod stmt;
+pub mod token;
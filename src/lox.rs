@@ -1,20 +1,43 @@
 use std::{
     fs::File,
     io::{self, Read},
+    process,
 };
 use text_io::read;
 
-use crate::{interpreter::Interpreter, parser::parser::Parser, resolver::Resolver, scanner};
+use crate::{
+    diagnostic::{use_color, DiagnosticFormat, Diagnostics},
+    interpreter::Interpreter,
+    parser::parser::Parser,
+    resolver::Resolver,
+    scanner,
+};
 
 #[derive(Debug)]
 pub struct Lox {
     pub interpretor: Interpreter,
+    pub had_error: bool,
+    pub had_runtime_error: bool,
+    pub diagnostic_format: DiagnosticFormat,
+    // Expression ids index the interpreter's resolved locals, so they must stay unique
+    // across runs (REPL lines, embedder calls) that share one interpreter.
+    next_expr_id: usize,
+}
+
+impl Default for Lox {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Lox {
     pub fn new() -> Self {
         Self {
             interpretor: Interpreter::new(),
+            had_error: false,
+            had_runtime_error: false,
+            diagnostic_format: DiagnosticFormat::Human,
+            next_expr_id: 0,
         }
     }
 
@@ -24,7 +47,16 @@ impl Lox {
 
         file.read_to_string(&mut source)?;
 
-        self.run(source);
+        let diagnostics = self.run(&source);
+        self.report(&diagnostics, file_name, &source);
+
+        // Same exit codes as the reference implementation (sysexits.h EX_DATAERR / EX_SOFTWARE).
+        if self.had_error {
+            process::exit(65);
+        }
+        if self.had_runtime_error {
+            process::exit(70);
+        }
         return Ok(());
     }
 
@@ -41,26 +73,57 @@ impl Lox {
             {
                 break;
             }
-            self.run(line);
+            let diagnostics = self.run(&line);
+            self.report(&diagnostics, "<stdin>", &line);
+            self.had_error = false;
+            self.had_runtime_error = false;
         }
 
         Ok(())
     }
 
-    pub fn run(&mut self, source: String) {
-        let mut scanner = scanner::Scanner::new(source);
+    fn report(&self, diagnostics: &Diagnostics, file_name: &str, source: &str) {
+        match self.diagnostic_format {
+            DiagnosticFormat::Human => print!("{}", diagnostics.render(source, use_color())),
+            // Kept off stdout so tools don't have to separate it from the program's own output.
+            DiagnosticFormat::Json => eprint!("{}", diagnostics.to_json_lines(file_name)),
+        }
+    }
+
+    /// Runs `source` and returns everything reported along the way instead of printing it.
+    pub fn run(&mut self, source: &str) -> Diagnostics {
+        let mut diagnostics = Diagnostics::new();
+
+        let mut scanner = scanner::Scanner::new(source.to_string());
         let tokens = scanner.scan_tokens();
+        diagnostics.extend(std::mem::take(&mut scanner.diagnostics));
 
         let mut parser = Parser::new(tokens);
-        let statements = parser.parse();
+        parser.set_first_id(self.next_expr_id);
+        let (statements, errors) = parser.parse();
+        self.next_expr_id = parser.next_id();
+        for error in &errors {
+            diagnostics.push(error.to_diagnostic());
+        }
+        if diagnostics.has_errors() {
+            self.had_error = true;
+            return diagnostics;
+        }
 
         let mut resolver = Resolver::new(&mut self.interpretor);
+        resolver.resolve_stmts(&statements);
+        diagnostics.extend(std::mem::take(&mut resolver.diagnostics));
+        if diagnostics.has_errors() {
+            self.had_error = true;
+            return diagnostics;
+        }
 
-        if let Some(stmts) = statements {
-            resolver.resolve_stmts(&stmts);
-            if !resolver.had_error {
-                self.interpretor.interpret(stmts);
-            }
+        self.interpretor.interpret(statements);
+        let runtime = std::mem::take(&mut self.interpretor.diagnostics);
+        if runtime.has_errors() {
+            self.had_runtime_error = true;
         }
+        diagnostics.extend(runtime);
+        diagnostics
     }
 }
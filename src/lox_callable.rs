@@ -1,15 +1,49 @@
-use std::{fmt::Debug, rc::Rc};
+use std::{
+    fmt::{Debug, Display},
+    rc::Rc,
+};
 
-use crate::{error::RunTimeError, expr::LiteralValue, interpreter::Interpreter};
+use crate::{
+    error::RunTimeError, expr::LiteralValue, interpreter::Interpreter, lox_class::LoxClass,
+};
 
 pub enum Callable {
     Function(Rc<dyn LoxCallable>),
+    Class(Rc<LoxClass>),
+}
+
+impl Callable {
+    pub fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: &[LiteralValue],
+    ) -> Result<LiteralValue, RunTimeError> {
+        match self {
+            Callable::Function(function) => function.call(interpreter, arguments),
+            Callable::Class(class) => LoxClass::instantiate(class, interpreter, arguments),
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            Callable::Function(function) => function.arity(),
+            Callable::Class(class) => class.arity(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Callable::Function(function) => function.name(),
+            Callable::Class(class) => class.name.clone(),
+        }
+    }
 }
 
 impl Clone for Callable {
     fn clone(&self) -> Self {
         match self {
             Callable::Function(f) => Callable::Function(Rc::clone(f)),
+            Callable::Class(c) => Callable::Class(Rc::clone(c)),
         }
     }
 }
@@ -17,6 +51,15 @@ impl Debug for Callable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Function(arg0) => f.debug_tuple("Function").field(&arg0.to_string()).finish(),
+            Self::Class(arg0) => f.debug_tuple("Class").field(&arg0.name).finish(),
+        }
+    }
+}
+impl Display for Callable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Function(function) => write!(f, "{}", function.to_string()),
+            Self::Class(class) => write!(f, "{}", class.name),
         }
     }
 }
@@ -28,21 +71,28 @@ pub trait LoxCallable {
         arguments: &[LiteralValue],
     ) -> Result<LiteralValue, RunTimeError>;
     fn arity(&self) -> usize;
+    fn name(&self) -> String;
     fn to_string(&self) -> String;
 }
 
 #[derive(Debug, Clone)]
 pub struct NativeFunction {
+    pub name: String,
     pub callable: fn(&mut Interpreter, &[LiteralValue]) -> Result<LiteralValue, RunTimeError>,
     pub params: usize,
 }
 
 impl NativeFunction {
     pub fn new(
+        name: &str,
         callable: fn(&mut Interpreter, &[LiteralValue]) -> Result<LiteralValue, RunTimeError>,
         params: usize,
     ) -> Self {
-        Self { callable, params }
+        Self {
+            name: name.to_string(),
+            callable,
+            params,
+        }
     }
 }
 
@@ -58,6 +108,9 @@ impl LoxCallable for NativeFunction {
     fn arity(&self) -> usize {
         self.params
     }
+    fn name(&self) -> String {
+        self.name.clone()
+    }
     fn to_string(&self) -> String {
         return "<Native Function>".to_string();
     }
@@ -5,6 +5,7 @@ use crate::{
     expr::LiteralValue,
     interpreter::Interpreter,
     lox_callable::LoxCallable,
+    lox_instance::LoxInstance,
     stmt::FunctionStmt,
     Environment::{Env, Environment},
 };
@@ -12,15 +13,36 @@ use crate::{
 pub struct LoxFunction {
     pub declaration: Rc<FunctionStmt>,
     pub closure: Env,
+    pub is_initializer: bool,
 }
 
 impl LoxFunction {
-    pub fn new(declaration: Rc<FunctionStmt>, closure: Env) -> Self {
+    pub fn new(declaration: Rc<FunctionStmt>, closure: Env, is_initializer: bool) -> Self {
         Self {
             declaration,
             closure,
+            is_initializer,
         }
     }
+
+    pub fn bind(&self, instance: Rc<RefCell<LoxInstance>>) -> LoxFunction {
+        let env = Rc::new(RefCell::new(Environment::new(Some(Rc::clone(
+            &self.closure,
+        )))));
+        env.borrow_mut()
+            .define("this".to_string(), Some(LiteralValue::Instance(instance)));
+
+        LoxFunction::new(Rc::clone(&self.declaration), env, self.is_initializer)
+    }
+
+    fn this(&self) -> LiteralValue {
+        self.closure
+            .borrow()
+            .values
+            .get("this")
+            .cloned()
+            .unwrap_or(LiteralValue::Nil)
+    }
 }
 
 impl LoxCallable for LoxFunction {
@@ -44,6 +66,9 @@ impl LoxCallable for LoxFunction {
         let value = interpreter.exeucute_block(&self.declaration.body, env)?;
         if let Some(value) = value {
             match value {
+                crate::stmt::ControlFlow::Return(_) if self.is_initializer => {
+                    return Ok(self.this());
+                }
                 crate::stmt::ControlFlow::Return(literal_value) => {
                     return Ok(literal_value.unwrap_or(LiteralValue::Nil));
                 }
@@ -52,6 +77,8 @@ impl LoxCallable for LoxFunction {
                     "Expect return expretion".to_string(),
                 )),
             }
+        } else if self.is_initializer {
+            return Ok(self.this());
         } else {
             return Ok(LiteralValue::Nil);
         }
@@ -60,6 +87,10 @@ impl LoxCallable for LoxFunction {
         return self.declaration.params.len();
     }
 
+    fn name(&self) -> String {
+        self.declaration.name.lexeme.clone()
+    }
+
     fn to_string(&self) -> String {
         return format!("<fn {}>", self.declaration.name.lexeme);
     }
@@ -0,0 +1,52 @@
+use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
+
+use crate::{
+    error::RunTimeError, expr::LiteralValue, lox_callable::Callable, lox_class::LoxClass,
+    token::Token,
+};
+
+#[derive(Debug)]
+pub struct LoxInstance {
+    pub class: Rc<LoxClass>,
+    pub fields: HashMap<String, LiteralValue>,
+}
+
+impl LoxInstance {
+    pub fn new(class: Rc<LoxClass>) -> Self {
+        Self {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+
+    /// Fields shadow methods; methods are bound to `instance` so `this` refers to it.
+    pub fn get(
+        instance: &Rc<RefCell<LoxInstance>>,
+        name: &Token,
+    ) -> Result<LiteralValue, RunTimeError> {
+        if let Some(value) = instance.borrow().fields.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        let method = instance.borrow().class.find_method(&name.lexeme);
+        match method {
+            Some(method) => Ok(LiteralValue::Callable(Callable::Function(Rc::new(
+                method.bind(Rc::clone(instance)),
+            )))),
+            None => Err(RunTimeError::new(
+                name.clone(),
+                "Undefined property '".to_string() + &name.lexeme + "'.",
+            )),
+        }
+    }
+
+    pub fn set(&mut self, name: &Token, value: LiteralValue) {
+        self.fields.insert(name.lexeme.clone(), value);
+    }
+}
+
+impl Display for LoxInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} instance", self.class.name)
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use crate::expr::LiteralValue;
+
+/// The values that can be used as map keys. Numbers are stored by their bits, with `-0` folded
+/// into `0` so the two keys match just as `-0 == 0` does.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    String(String),
+    Number(u64),
+    Boolean(bool),
+    Nil,
+}
+
+impl MapKey {
+    /// Returns `None` for values that can't be keys: lists, maps, functions, instances and NaN.
+    pub fn from_value(value: &LiteralValue) -> Option<Self> {
+        match value {
+            LiteralValue::String(str) => Some(MapKey::String(str.clone())),
+            // NaN isn't equal to itself, so it could never be looked up again.
+            LiteralValue::Number(num) if num.is_nan() => None,
+            LiteralValue::Number(num) if *num == 0.0 => Some(MapKey::Number(0.0f64.to_bits())),
+            LiteralValue::Number(num) => Some(MapKey::Number(num.to_bits())),
+            LiteralValue::Boolean(bol) => Some(MapKey::Boolean(*bol)),
+            LiteralValue::Nil => Some(MapKey::Nil),
+            _ => None,
+        }
+    }
+
+    pub fn to_value(&self) -> LiteralValue {
+        match self {
+            MapKey::String(str) => LiteralValue::String(str.clone()),
+            MapKey::Number(bits) => LiteralValue::Number(f64::from_bits(*bits)),
+            MapKey::Boolean(bol) => LiteralValue::Boolean(*bol),
+            MapKey::Nil => LiteralValue::Nil,
+        }
+    }
+}
+
+/// A map that remembers insertion order, so printing and iterating are deterministic.
+/// Assigning to an existing key keeps its original position.
+#[derive(Debug, Clone, Default)]
+pub struct LoxMap {
+    entries: Vec<(MapKey, LiteralValue)>,
+    positions: HashMap<MapKey, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<&LiteralValue> {
+        self.positions
+            .get(key)
+            .map(|position| &self.entries[*position].1)
+    }
+
+    pub fn insert(&mut self, key: MapKey, value: LiteralValue) {
+        match self.positions.get(&key) {
+            Some(position) => self.entries[*position].1 = value,
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries in insertion order.
+    pub fn entries(&self) -> &[(MapKey, LiteralValue)] {
+        &self.entries
+    }
+}
@@ -1,17 +1,25 @@
 // to remove the warnings
 // #![allow(warnings)]
 
-use std::env::{self};
+use std::{
+    env::{self},
+    process,
+};
 
+use diagnostic::DiagnosticFormat;
 use lox::Lox;
 
 mod Environment;
+mod diagnostic;
 mod error;
 mod expr;
 mod interpreter;
 mod lox;
 mod lox_callable;
+mod lox_class;
 mod lox_function;
+mod lox_instance;
+mod lox_map;
 mod parser;
 mod resolver;
 mod scanner;
@@ -19,14 +27,26 @@ mod stmt;
 mod token;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut diagnostic_format = DiagnosticFormat::Human;
+    let mut script = None;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--json-diagnostics" => diagnostic_format = DiagnosticFormat::Json,
+            flag if flag.starts_with("--") => {
+                eprintln!("Usage: lox-lang [--json-diagnostics] [script]");
+                process::exit(64);
+            }
+            _ => script = Some(arg),
+        }
+    }
 
     let mut lox = Lox::new();
+    lox.diagnostic_format = diagnostic_format;
 
-    if args.len() <= 1 {
-        lox.run_prompt().expect("Someting went wrong");
-    } else {
-        lox.run_file(&args[args.len() - 1])
-            .expect("Someting went wrong while reading");
+    match script {
+        None => lox.run_prompt().expect("Someting went wrong"),
+        Some(script) => lox
+            .run_file(&script)
+            .expect("Someting went wrong while reading"),
     }
 }
@@ -1,26 +1,32 @@
 use core::fmt;
 
-use crate::{error::LoxError, token::Token};
+use crate::{
+    diagnostic::{Diagnostic, Phase},
+    token::Token,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParserError {
-    token: Token,
-    message: String,
+    pub token: Token,
+    pub message: String,
 }
 
 impl ParserError {
     pub fn new(token: Token, message: String) -> Self {
-        LoxError::token_errro(&token, message.to_string());
         Self { token, message }
     }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::error_at_token(Phase::Parse, &self.token, self.message.clone())
+    }
 }
 
 impl fmt::Display for ParserError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "({}) line: {} => ({})",
-            self.token.lexeme, self.token.line, self.message
+            "({}) line: {}, column: {} => ({})",
+            self.token.lexeme, self.token.line, self.token.span.column, self.message
         )
     }
 }
@@ -1,4 +1,4 @@
-use crate::stmt::Stmt;
+use crate::stmt::{FunctionStmt, Stmt};
 use crate::token::{Token, TokenType};
 
 use crate::expr::{self, Expr, ExprKind, LiteralExpr, LiteralValue};
@@ -10,7 +10,7 @@ pub struct Parser {
     tokens: Vec<Token>,
     curr: usize,
     next_id: usize,
-    pub had_error: bool,
+    errors: Vec<ParserError>,
 }
 
 type ParserResult<T> = std::result::Result<T, ParserError>;
@@ -20,44 +20,96 @@ impl Parser {
         Parser {
             curr: 0,
             tokens,
-            had_error: false,
+            errors: vec![],
             next_id: 0,
         }
     }
+
+    /// Numbers expressions from `first_id` so they don't collide with ids handed out by an
+    /// earlier parser feeding the same interpreter.
+    pub fn set_first_id(&mut self, first_id: usize) {
+        self.next_id = first_id;
+    }
+
     pub fn next_id(&mut self) -> usize {
         let id = self.next_id;
         self.next_id += 1;
         id
     }
 
-    pub fn parse(&mut self) -> Option<Vec<Stmt>> {
+    /// Parses the whole program, recovering after each syntax error so that every error is
+    /// reported. The statements are only complete when the error list is empty.
+    pub fn parse(&mut self) -> (Vec<Stmt>, Vec<ParserError>) {
         let mut statments: Vec<Stmt> = vec![];
         while !self.is_at_end() {
             match self.declaration() {
                 Ok(value) => statments.push(value),
-                Err(_) => {
+                Err(err) => {
+                    self.errors.push(err);
                     self.synchronize();
-                    return None;
+                    // A stray `}` has no block to close at the top level.
+                    if self.check(TokenType::RIGHTBRACE) {
+                        self.advance();
+                    }
                 }
             }
         }
-        return Some(statments);
+        return (statments, std::mem::take(&mut self.errors));
     }
     fn declaration(&mut self) -> ParserResult<Stmt> {
-        if self.match_token_types(vec![TokenType::FUN]) {
-            return self.function("function");
+        if self.match_token_types(vec![TokenType::CLASS]) {
+            return self.class_declaration();
+        }
+        // `fun (` starts a lambda in an expression statement rather than a declaration.
+        if self.check(TokenType::FUN)
+            && !self
+                .tokens
+                .get(self.curr + 1)
+                .is_some_and(|token| token.token_type == TokenType::LEFTPAREN)
+        {
+            self.advance();
+            let FunctionStmt { name, params, body } = self.function("function")?;
+            return Ok(Stmt::function_stmt(name, params, body));
         }
         if self.match_token_types(vec![TokenType::VAR]) {
             return self.var_declaration();
         }
         return self.statment();
     }
+    fn class_declaration(&mut self) -> ParserResult<Stmt> {
+        let name = self.consume(TokenType::IDENTIFIER, "Expect class name.".to_string())?;
+
+        let mut superclass = None;
+        if self.match_token_types(vec![TokenType::LESS]) {
+            let superclass_name =
+                self.consume(TokenType::IDENTIFIER, "Expect superclass name.".to_string())?;
+            superclass = Some(Expr::variable(self.next_id(), superclass_name));
+        }
+
+        self.consume(
+            TokenType::LEFTBRACE,
+            "Expect '{' before class body.".to_string(),
+        )?;
+
+        let mut methods = vec![];
+        while !self.check(TokenType::RIGHTBRACE) && !self.is_at_end() {
+            methods.push(self.function("method")?);
+        }
+
+        self.consume(
+            TokenType::RIGHTBRACE,
+            "Expect '}' after class body.".to_string(),
+        )?;
+
+        return Ok(Stmt::class_stmt(name, superclass, methods));
+    }
     fn var_declaration(&mut self) -> ParserResult<Stmt> {
         let name = self.consume(TokenType::IDENTIFIER, "Expect variable name.".to_string());
         let mut initializer: Option<Expr> = None;
 
         if self.match_token_types(vec![TokenType::EQUAL]) {
-            initializer = Some(self.expression()?);
+            // `var a = 1, b = 2;` is not a declaration list, so don't let the comma operator swallow it.
+            initializer = Some(self.assignment()?);
         }
         self.consume(
             TokenType::SEMICOLON,
@@ -77,7 +129,7 @@ impl Parser {
         )?;
         let body = self.statment()?;
 
-        return Ok(Stmt::while_stmt(condition, body));
+        return Ok(Stmt::while_stmt(condition, body, None));
     }
     fn statment(&mut self) -> ParserResult<Stmt> {
         if self.match_token_types(vec![TokenType::FOR]) {
@@ -95,18 +147,33 @@ impl Parser {
         if self.match_token_types(vec![TokenType::WHILE]) {
             return self.while_statement();
         }
-        if self.match_token_types(vec![TokenType::LEFTBRACE]) {
+        if self.check(TokenType::LEFTBRACE) && !self.starts_map_literal() {
+            self.advance();
             return Ok(Stmt::block_stmt(self.block()));
         }
         if self.match_token_types(vec![TokenType::BREAK]) {
-            println!("{:?} , {:?}", self.previous(), self.peek());
-            self.consume(TokenType::SEMICOLON, "Expect ; after break".to_string())?;
-            return Ok(Stmt::break_stmt());
+            let keyword = self.previous();
+            self.consume(
+                TokenType::SEMICOLON,
+                "Expect ';' after 'break'.".to_string(),
+            )?;
+            return Ok(Stmt::break_stmt(keyword));
+        }
+        if self.match_token_types(vec![TokenType::CONTINUE]) {
+            let keyword = self.previous();
+            self.consume(
+                TokenType::SEMICOLON,
+                "Expect ';' after 'continue'.".to_string(),
+            )?;
+            return Ok(Stmt::continue_stmt(keyword));
         }
         return self.expression_statment();
     }
     fn for_statement(&mut self) -> ParserResult<Stmt> {
         self.consume(TokenType::LEFTPAREN, "Expect '(' after 'for'.".to_string())?;
+        if self.starts_for_in() {
+            return self.for_in_statement();
+        }
         let mut initializer = None;
 
         if self.match_token_types(vec![TokenType::SEMICOLON]) {
@@ -137,10 +204,6 @@ impl Parser {
 
         let mut body = self.statment()?;
 
-        if let Some(inc) = increment {
-            body = Stmt::block_stmt(vec![body, Stmt::expresstion_stmt(inc)]);
-        }
-
         if condition.is_none() {
             condition = Some(Expr {
                 id: self.next_id,
@@ -149,13 +212,44 @@ impl Parser {
                 }),
             });
         }
-        body = Stmt::while_stmt(condition.unwrap(), body);
+        body = Stmt::while_stmt(condition.unwrap(), body, increment);
 
         if let Some(init) = initializer {
             body = Stmt::block_stmt(vec![init, body]);
         }
         Ok(body)
     }
+    /// `in` is only special right after `for (var name`, so it stays usable as a variable name.
+    fn starts_for_in(&self) -> bool {
+        match self.tokens.get(self.curr..self.curr + 3) {
+            Some([var, name, keyword]) => {
+                var.token_type == TokenType::VAR
+                    && name.token_type == TokenType::IDENTIFIER
+                    && keyword.token_type == TokenType::IDENTIFIER
+                    && keyword.lexeme == "in"
+            }
+            _ => false,
+        }
+    }
+    /// Parses the rest of `for (var name in iterable) body`; `starts_for_in` has already
+    /// checked the first three tokens.
+    fn for_in_statement(&mut self) -> ParserResult<Stmt> {
+        self.advance();
+        let name = self.advance();
+        let keyword = self.advance();
+        let iterable = self.expression()?;
+        let mut range_end = None;
+        if self.match_token_types(vec![TokenType::DOTDOT]) {
+            range_end = Some(self.expression()?);
+        }
+        self.consume(
+            TokenType::RIGHTPAREN,
+            "Expect ')' after for-in clause.".to_string(),
+        )?;
+
+        let body = self.statment()?;
+        Ok(Stmt::for_in_stmt(name, keyword, iterable, range_end, body))
+    }
     fn if_statment(&mut self) -> ParserResult<Stmt> {
         self.consume(TokenType::LEFTPAREN, "Expect '(' after 'if'.".to_string())?;
         let condition = self.expression()?;
@@ -195,7 +289,7 @@ impl Parser {
     }
 
     fn expression_statment(&mut self) -> ParserResult<Stmt> {
-        let expr = self.assignment()?;
+        let expr = self.expression()?;
         self.consume(
             TokenType::SEMICOLON,
             "Expect ';' after expression.".to_string(),
@@ -203,22 +297,27 @@ impl Parser {
         Ok(Stmt::expresstion_stmt(expr))
     }
 
-    fn function(&mut self, kind: &str) -> ParserResult<Stmt> {
+    fn function(&mut self, kind: &str) -> ParserResult<FunctionStmt> {
         let name = self.consume(TokenType::IDENTIFIER, format!("Expect {} name.", kind))?;
         self.consume(
             TokenType::LEFTPAREN,
             format!("Expect ( after {} name", kind),
         )?;
-
+        self.function_body(name, kind)
+    }
+    /// Parses the parameters and block of a function after its `(`. Lambdas share this with
+    /// named functions and methods.
+    fn function_body(&mut self, name: Token, kind: &str) -> ParserResult<FunctionStmt> {
         let mut parameters = vec![];
 
         if !self.check(TokenType::RIGHTPAREN) {
             loop {
                 if parameters.len() >= 255 {
-                    return Err(ParserError::new(
+                    let err = ParserError::new(
                         self.peek().clone(),
                         "Can't have more than 255 parameters.".to_string(),
-                    ));
+                    );
+                    self.errors.push(err);
                 }
 
                 parameters.push(
@@ -242,7 +341,11 @@ impl Parser {
 
         let body = self.block();
 
-        return Ok(Stmt::function_stmt(name, parameters, body));
+        return Ok(FunctionStmt {
+            name,
+            params: parameters,
+            body,
+        });
     }
 
     fn block(&mut self) -> Vec<Stmt> {
@@ -252,24 +355,24 @@ impl Parser {
                 Ok(stmt) => {
                     statements.push(stmt);
                 }
-                Err(_) => {
+                Err(err) => {
+                    self.errors.push(err);
                     self.synchronize();
-                    return statements;
                 }
             }
         }
 
         match self.consume(TokenType::RIGHTBRACE, "Expect '}' after block.".to_string()) {
             Ok(_) => return statements,
-            Err(_) => {
-                self.synchronize();
+            Err(err) => {
+                self.errors.push(err);
                 return statements;
             }
         }
     }
 
     fn assignment(&mut self) -> Result<Expr, ParserError> {
-        let expr = self.or()?;
+        let expr = self.ternary()?;
 
         if self.match_token_types(vec![TokenType::EQUAL]) {
             let equals = self.previous();
@@ -279,17 +382,76 @@ impl Parser {
                     let name = variable_expr.name.clone();
                     return Ok(Expr::assign(self.next_id(), name, value));
                 }
+                ExprKind::Get(get_expr) => {
+                    let object = *get_expr.object.clone();
+                    let name = get_expr.name.clone();
+                    return Ok(Expr::set(self.next_id(), object, name, value));
+                }
+                ExprKind::Index(index_expr) => {
+                    let object = *index_expr.object.clone();
+                    let bracket = index_expr.bracket.clone();
+                    let index = *index_expr.index.clone();
+                    return Ok(Expr::index_set(
+                        self.next_id(),
+                        object,
+                        bracket,
+                        index,
+                        value,
+                    ));
+                }
                 _ => {
-                    return Err(ParserError::new(
+                    // The parser isn't confused here, so report it and carry on.
+                    self.errors.push(ParserError::new(
                         equals,
                         "Invalid assignment target.".to_string(),
                     ));
                 }
             }
+        } else if self.match_token_types(vec![
+            TokenType::PLUSEQUAL,
+            TokenType::MINUSEQUAL,
+            TokenType::STAREQUAL,
+            TokenType::SLASHEQUAL,
+            TokenType::PERCENTEQUAL,
+        ]) {
+            let operator = self.previous();
+            let value = self.assignment()?;
+            if self.is_assignment_target(&expr, &operator) {
+                return Ok(Expr::compound_assign(self.next_id(), expr, operator, value));
+            }
         }
 
         return Ok(expr);
     }
+    /// Compound assignments and `++`/`--` write back to a variable, property or index. Anything
+    /// else is reported without unwinding, like a bad `=` target.
+    fn is_assignment_target(&mut self, expr: &Expr, operator: &Token) -> bool {
+        match expr.kind {
+            ExprKind::Variable(_) | ExprKind::Get(_) | ExprKind::Index(_) => true,
+            _ => {
+                self.errors.push(ParserError::new(
+                    operator.clone(),
+                    "Invalid assignment target.".to_string(),
+                ));
+                false
+            }
+        }
+    }
+    fn ternary(&mut self) -> ParserResult<Expr> {
+        let condition = self.or()?;
+
+        if self.match_token_types(vec![TokenType::QUESTION]) {
+            let left = self.expression()?;
+            self.consume(
+                TokenType::COLON,
+                "Expect ':' after then branch of ternary expression.".to_string(),
+            )?;
+            let right = self.ternary()?;
+            return Ok(Expr::ternary(self.next_id(), condition, left, right));
+        }
+
+        Ok(condition)
+    }
     fn or(&mut self) -> ParserResult<Expr> {
         let mut expr = self.and()?;
         while self.match_token_types(vec![TokenType::OR]) {
@@ -310,7 +472,17 @@ impl Parser {
         Ok(expr)
     }
     fn expression(&mut self) -> Result<Expr, ParserError> {
-        return self.assignment();
+        return self.separator();
+    }
+
+    fn separator(&mut self) -> ParserResult<Expr> {
+        let mut expr = self.assignment()?;
+
+        while self.match_token_types(vec![TokenType::COMMA]) {
+            let right = self.assignment()?;
+            expr = Expr::separator(self.next_id(), expr, right);
+        }
+        Ok(expr)
     }
 
     fn equality(&mut self) -> Result<Expr, ParserError> {
@@ -326,7 +498,7 @@ impl Parser {
     }
 
     fn comparison(&mut self) -> Result<Expr, ParserError> {
-        let mut expr = self.term()?;
+        let mut expr = self.bitwise_or()?;
 
         while self.match_token_types(vec![
             TokenType::GREATER,
@@ -334,6 +506,51 @@ impl Parser {
             TokenType::LESS,
             TokenType::LESSEQUAL,
         ]) {
+            let operator = self.previous();
+            let right = self.bitwise_or()?;
+            expr = Expr::binary(self.next_id(), expr, operator, right);
+        }
+        Ok(expr)
+    }
+
+    // Bitwise operators bind tighter than comparisons, so `x & 1 == 0` means `(x & 1) == 0`.
+    fn bitwise_or(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.bitwise_xor()?;
+
+        while self.match_token_types(vec![TokenType::PIPE]) {
+            let operator = self.previous();
+            let right = self.bitwise_xor()?;
+            expr = Expr::binary(self.next_id(), expr, operator, right);
+        }
+        Ok(expr)
+    }
+
+    fn bitwise_xor(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.bitwise_and()?;
+
+        while self.match_token_types(vec![TokenType::CARET]) {
+            let operator = self.previous();
+            let right = self.bitwise_and()?;
+            expr = Expr::binary(self.next_id(), expr, operator, right);
+        }
+        Ok(expr)
+    }
+
+    fn bitwise_and(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.shift()?;
+
+        while self.match_token_types(vec![TokenType::AMPERSAND]) {
+            let operator = self.previous();
+            let right = self.shift()?;
+            expr = Expr::binary(self.next_id(), expr, operator, right);
+        }
+        Ok(expr)
+    }
+
+    fn shift(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.term()?;
+
+        while self.match_token_types(vec![TokenType::LESSLESS, TokenType::GREATERGREATER]) {
             let operator = self.previous();
             let right = self.term()?;
             expr = Expr::binary(self.next_id(), expr, operator, right);
@@ -355,9 +572,14 @@ impl Parser {
     fn factor(&mut self) -> Result<Expr, ParserError> {
         let mut expr = self.unary()?;
 
-        while self.match_token_types(vec![TokenType::STAR, TokenType::SLASH]) {
+        while self.match_token_types(vec![
+            TokenType::STAR,
+            TokenType::SLASH,
+            TokenType::PERCENT,
+            TokenType::TILDESLASH,
+        ]) {
             let operator = self.previous();
-            let right = self.factor()?;
+            let right = self.unary()?;
             expr = Expr::binary(self.next_id(), expr, operator, right);
         }
 
@@ -365,13 +587,35 @@ impl Parser {
     }
 
     fn unary(&mut self) -> Result<Expr, ParserError> {
-        if self.match_token_types(vec![TokenType::BANG, TokenType::MINUS]) {
+        if self.match_token_types(vec![TokenType::BANG, TokenType::MINUS, TokenType::TILDE]) {
             let operator = self.previous();
             let right = self.unary()?;
             return Ok(Expr::unary(self.next_id(), operator, right));
         }
+        if self.match_token_types(vec![TokenType::PLUSPLUS, TokenType::MINUSMINUS]) {
+            let operator = self.previous();
+            let target = self.unary()?;
+            if !self.is_assignment_target(&target, &operator) {
+                return Ok(target);
+            }
+            return Ok(Expr::update(self.next_id(), target, operator, true));
+        }
+
+        self.power()
+    }
+
+    // `**` binds tighter than a unary on its left and is right-associative:
+    // `-2 ** 2` is `-(2 ** 2)` and `2 ** 3 ** 2` is `2 ** (3 ** 2)`.
+    fn power(&mut self) -> Result<Expr, ParserError> {
+        let expr = self.call()?;
+
+        if self.match_token_types(vec![TokenType::STARSTAR]) {
+            let operator = self.previous();
+            let right = self.unary()?;
+            return Ok(Expr::binary(self.next_id(), expr, operator, right));
+        }
 
-        self.call()
+        Ok(expr)
     }
 
     fn finish_call(&mut self, callee: Expr) -> ParserResult<Expr> {
@@ -380,12 +624,14 @@ impl Parser {
         if !self.check(TokenType::RIGHTPAREN) {
             loop {
                 if arguments.len() >= 255 {
-                    return Err(ParserError::new(
+                    let err = ParserError::new(
                         self.peek().clone(),
                         "Can't have more than 255 arguments.".to_string(),
-                    ));
+                    );
+                    self.errors.push(err);
                 }
-                arguments.push(self.expression()?);
+                // Arguments are parsed above the comma operator so `,` keeps separating them.
+                arguments.push(self.assignment()?);
 
                 if !self.match_token_types(vec![TokenType::COMMA]) {
                     break;
@@ -407,11 +653,32 @@ impl Parser {
         loop {
             if self.match_token_types(vec![TokenType::LEFTPAREN]) {
                 expr = self.finish_call(expr)?;
+            } else if self.match_token_types(vec![TokenType::DOT]) {
+                let name = self.consume(
+                    TokenType::IDENTIFIER,
+                    "Expect property name after '.'.".to_string(),
+                )?;
+                expr = Expr::get(self.next_id(), expr, name);
+            } else if self.match_token_types(vec![TokenType::LEFTBRACKET]) {
+                let bracket = self.previous();
+                let index = self.expression()?;
+                self.consume(
+                    TokenType::RIGHTBRACKET,
+                    "Expect ']' after index.".to_string(),
+                )?;
+                expr = Expr::index(self.next_id(), expr, bracket, index);
             } else {
                 break;
             }
         }
 
+        if self.match_token_types(vec![TokenType::PLUSPLUS, TokenType::MINUSMINUS]) {
+            let operator = self.previous();
+            if self.is_assignment_target(&expr, &operator) {
+                return Ok(Expr::update(self.next_id(), expr, operator, false));
+            }
+        }
+
         Ok(expr)
     }
 
@@ -443,39 +710,189 @@ impl Parser {
                 }
             }
         }
-        if self.match_token_types(vec![TokenType::COMMA]) {
-            println!("here: =>>")
+        if self.match_token_types(vec![TokenType::INTERPOLATION]) {
+            return self.interpolation();
+        }
+        if self.match_token_types(vec![TokenType::LEFTBRACE]) {
+            return self.map_literal();
+        }
+        if self.match_token_types(vec![TokenType::LEFTBRACKET]) {
+            let mut elements = vec![];
+            // A trailing comma is allowed, so check for `]` before each element.
+            while !self.check(TokenType::RIGHTBRACKET) {
+                elements.push(self.assignment()?);
+                if !self.match_token_types(vec![TokenType::COMMA]) {
+                    break;
+                }
+            }
+            self.consume(
+                TokenType::RIGHTBRACKET,
+                "Expect ']' after list elements.".to_string(),
+            )?;
+            return Ok(Expr::list(self.next_id(), elements));
+        }
+        if self.match_token_types(vec![TokenType::SUPER]) {
+            let keyword = self.previous();
+            self.consume(TokenType::DOT, "Expect '.' after 'super'.".to_string())?;
+            let method = self.consume(
+                TokenType::IDENTIFIER,
+                "Expect superclass method name.".to_string(),
+            )?;
+            return Ok(Expr::super_expr(self.next_id(), keyword, method));
+        }
+
+        if self.match_token_types(vec![TokenType::THIS]) {
+            return Ok(Expr::this(self.next_id(), self.previous()));
         }
 
         if self.match_token_types(vec![TokenType::IDENTIFIER]) {
             return Ok(Expr::variable(self.next_id(), self.previous()));
         }
 
+        if self.match_token_types(vec![TokenType::FUN]) {
+            let keyword = self.previous();
+            let name = self.lambda_name(keyword);
+            self.consume(TokenType::LEFTPAREN, "Expect '(' after 'fun'.".to_string())?;
+            let declaration = self.function_body(name, "lambda")?;
+            return Ok(Expr::lambda(self.next_id(), declaration));
+        }
+
+        if self.check(TokenType::LEFTPAREN) && self.starts_arrow_function() {
+            return self.arrow_function();
+        }
+
         if self.match_token_types(vec![TokenType::LEFTPAREN]) {
             let expr = self.expression()?;
 
-            match self.consume(
+            self.consume(
                 TokenType::RIGHTPAREN,
                 "Expect ')' after expression.".to_string(),
-            ) {
-                Ok(_) => return Ok(Expr::grouping(self.next_id(), expr)),
-                Err(err) => {
-                    self.had_error = true;
-                    return Err(err);
-                }
-            }
+            )?;
+            return Ok(Expr::grouping(self.next_id(), expr));
         }
 
         if self.match_token_types(vec![TokenType::EOF]) {}
 
-        self.had_error = true;
-
         Err(ParserError::new(
             self.peek().clone(),
             "Expect expression.".to_string(),
         ))
     }
 
+    /// `(a, b) => expr` starts like a grouping, so look past the parameter list for the `=>`.
+    fn starts_arrow_function(&self) -> bool {
+        let mut i = self.curr + 1;
+        let token_type = |i: usize| self.tokens.get(i).map(|token| &token.token_type);
+        if token_type(i) != Some(&TokenType::RIGHTPAREN) {
+            loop {
+                if token_type(i) != Some(&TokenType::IDENTIFIER) {
+                    return false;
+                }
+                i += 1;
+                match token_type(i) {
+                    Some(TokenType::COMMA) => i += 1,
+                    Some(TokenType::RIGHTPAREN) => break,
+                    _ => return false,
+                }
+            }
+        }
+        token_type(i + 1) == Some(&TokenType::ARROW)
+    }
+    /// Parses `(params) => expr` into a function whose body returns `expr`.
+    fn arrow_function(&mut self) -> ParserResult<Expr> {
+        self.advance();
+        let mut params = vec![];
+        while !self.match_token_types(vec![TokenType::RIGHTPAREN]) {
+            if params.len() >= 255 {
+                let err = ParserError::new(
+                    self.peek().clone(),
+                    "Can't have more than 255 parameters.".to_string(),
+                );
+                self.errors.push(err);
+            }
+            params.push(self.advance());
+            self.match_token_types(vec![TokenType::COMMA]);
+        }
+        let arrow = self.advance();
+        let value = self.assignment()?;
+
+        let declaration = FunctionStmt {
+            name: self.lambda_name(arrow.clone()),
+            params,
+            body: vec![Stmt::return_stmt(arrow, Some(value))],
+        };
+        Ok(Expr::lambda(self.next_id(), declaration))
+    }
+    /// Lambdas have no name of their own; they print and show up in stack traces as `lambda`.
+    fn lambda_name(&self, keyword: Token) -> Token {
+        Token {
+            lexeme: "lambda".to_string(),
+            ..keyword
+        }
+    }
+
+    /// Parses the entries of a map literal after its `{`. A trailing comma is allowed.
+    fn map_literal(&mut self) -> ParserResult<Expr> {
+        let brace = self.previous();
+        let mut entries = vec![];
+        while !self.check(TokenType::RIGHTBRACE) {
+            let key = self.assignment()?;
+            self.consume(TokenType::COLON, "Expect ':' after map key.".to_string())?;
+            let value = self.assignment()?;
+            entries.push((key, value));
+            if !self.match_token_types(vec![TokenType::COMMA]) {
+                break;
+            }
+        }
+        self.consume(
+            TokenType::RIGHTBRACE,
+            "Expect '}' after map entries.".to_string(),
+        )?;
+        Ok(Expr::map(self.next_id(), brace, entries))
+    }
+
+    /// In statement position `{` opens a block, unless it is followed by an expression and a
+    /// `:`, which no statement can start with. This lets `{"a": 1}["a"];` and `{(k): 1};` be
+    /// expression statements, while `{}` and any other `{` stay blocks. The key is parsed
+    /// speculatively and everything it consumed or reported is rolled back afterwards.
+    fn starts_map_literal(&mut self) -> bool {
+        let (curr, next_id, errors) = (self.curr, self.next_id, self.errors.len());
+        self.advance();
+        let is_map = self.assignment().is_ok() && self.check(TokenType::COLON);
+        self.curr = curr;
+        self.next_id = next_id;
+        self.errors.truncate(errors);
+        is_map
+    }
+
+    /// `"a ${b} c"` arrives as INTERPOLATION("a "), the tokens of `b`, `}`, then STRING(" c").
+    fn interpolation(&mut self) -> ParserResult<Expr> {
+        let mut parts = vec![];
+        loop {
+            if let Some(crate::token::TokenLiteral::Text(text)) = self.previous().literal {
+                if !text.is_empty() {
+                    let id = self.next_id();
+                    parts.push(Expr::literal(id, LiteralValue::String(text)));
+                }
+            }
+            if self.previous().token_type == TokenType::STRING {
+                return Ok(Expr::interpolation(self.next_id(), parts));
+            }
+
+            parts.push(self.expression()?);
+            self.consume(
+                TokenType::RIGHTBRACE,
+                "Expect '}' after interpolated expression.".to_string(),
+            )?;
+            if !self.match_token_types(vec![TokenType::INTERPOLATION, TokenType::STRING]) {
+                return Err(ParserError::new(
+                    self.peek().clone(),
+                    "Expect rest of string after interpolation.".to_string(),
+                ));
+            }
+        }
+    }
+
     fn consume(
         &mut self,
         token_type: TokenType,
@@ -484,30 +901,43 @@ impl Parser {
         if self.check(token_type) {
             return Ok(self.advance());
         } else {
-            self.had_error = true;
             let err = ParserError::new(self.peek().clone(), error_message);
             Err(err)
         }
     }
 
+    /// Skips tokens until the start of the next statement. Nested `{ ... }` are skipped whole,
+    /// and a `}` that closes the enclosing block is left for `block` to consume.
     fn synchronize(&mut self) {
-        self.advance();
+        let mut depth = 0;
         while !self.is_at_end() {
-            if self.previous().token_type == TokenType::SEMICOLON {
-                return;
+            // The token that failed can itself start the next statement, e.g. a missing `;`.
+            if depth == 0 {
+                match self.peek().token_type {
+                    TokenType::IF
+                    | TokenType::FUN
+                    | TokenType::FOR
+                    | TokenType::CLASS
+                    | TokenType::WHILE
+                    | TokenType::PRINT
+                    | TokenType::RETURN
+                    | TokenType::VAR
+                    | TokenType::RIGHTBRACE => return,
+                    _ => {}
+                }
             }
-            match self.peek().token_type {
-                TokenType::IF
-                | TokenType::FUN
-                | TokenType::FOR
-                | TokenType::CLASS
-                | TokenType::WHILE
-                | TokenType::PRINT
-                | TokenType::RETURN
-                | TokenType::VAR => return,
+            match self.advance().token_type {
+                // Every `${` is closed by a `}` token, just like `{`.
+                TokenType::LEFTBRACE | TokenType::INTERPOLATION => depth += 1,
+                TokenType::RIGHTBRACE => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                TokenType::SEMICOLON if depth == 0 => return,
                 _ => {}
             }
-            self.advance();
         }
     }
 
@@ -1,38 +1,55 @@
 use std::{collections::HashMap, ops::Deref};
 
 use crate::{
-    error::LoxError,
+    diagnostic::{Diagnostic, Diagnostics, Label, Phase},
     expr::{
-        AssginExpr, BinaryExpr, CallExpr, Expr, ExprKind, GroupingExpr, LiteralExpr, LogicalExpr,
-        UnaryExpr, VariableExpr,
+        AssginExpr, BinaryExpr, CallExpr, CompoundAssignExpr, Expr, ExprKind, GetExpr,
+        GroupingExpr, IndexExpr, IndexSetExpr, InterpolationExpr, LambdaExpr, ListExpr,
+        LiteralExpr, LogicalExpr, MapExpr, SeparatorExpr, SetExpr, SuperExpr, TernaryExpr,
+        ThisExpr, UnaryExpr, UpdateExpr, VariableExpr,
     },
     interpreter::Interpreter,
     stmt::{
-        BlockStmt, ExpresstionStmt, FunctionStmt, IfStmt, PrintStmt, ReturnStmt, Stmt, StmtExpr,
-        VarStmt, WhileStmt,
+        BlockStmt, BreakStmt, ClassStmt, ContinueStmt, ExpresstionStmt, ForInStmt, FunctionStmt,
+        IfStmt, PrintStmt, ReturnStmt, Stmt, StmtExpr, VarStmt, WhileStmt,
     },
     token::Token,
 };
 
 pub struct Resolver<'a> {
-    pub had_error: bool,
+    pub diagnostics: Diagnostics,
     interpreter: &'a mut Interpreter,
     scopes: Vec<HashMap<String, bool>>,
+    // Where each name in the matching entry of `scopes` was declared, for diagnostics.
+    declarations: Vec<HashMap<String, Token>>,
     curr_function: Option<FunctionType>,
+    curr_class: Option<ClassType>,
+    in_loop: bool,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FunctionType {
     FUNCTION,
+    METHOD,
+    INITIALIZER,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClassType {
+    CLASS,
+    SUBCLASS,
 }
 
 impl<'a> Resolver<'a> {
     pub fn new(interpreter: &'a mut Interpreter) -> Self {
         Self {
-            had_error: false,
+            diagnostics: Diagnostics::new(),
             interpreter,
             scopes: vec![],
+            declarations: vec![],
             curr_function: None,
+            curr_class: None,
+            in_loop: false,
         }
     }
     fn visit_block_stmt(&mut self, stmt: &BlockStmt) {
@@ -49,6 +66,52 @@ impl<'a> Resolver<'a> {
 
         self.resolve_function(stmt, FunctionType::FUNCTION);
     }
+    fn visit_class_stmt(&mut self, stmt: &ClassStmt) {
+        let enclosing_class = self.curr_class;
+        self.curr_class = Some(ClassType::CLASS);
+
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+
+        if let Some(superclass) = &stmt.superclass {
+            if let ExprKind::Variable(variable) = &superclass.kind {
+                if variable.name.lexeme == stmt.name.lexeme {
+                    self.error(
+                        &variable.name,
+                        "A class can't inherit from itself.".to_string(),
+                    );
+                }
+            }
+
+            self.curr_class = Some(ClassType::SUBCLASS);
+            self.resolve_exper(superclass);
+
+            self.begin_scope();
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.insert("super".to_string(), true);
+            }
+        }
+
+        self.begin_scope();
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert("this".to_string(), true);
+        }
+
+        for method in &stmt.methods {
+            let declaration = if method.name.lexeme == "init" {
+                FunctionType::INITIALIZER
+            } else {
+                FunctionType::METHOD
+            };
+            self.resolve_function(method, declaration);
+        }
+
+        self.end_scope();
+        if stmt.superclass.is_some() {
+            self.end_scope();
+        }
+        self.curr_class = enclosing_class;
+    }
     fn visit_if_stmt(&mut self, stmt: &IfStmt) {
         self.resolve_exper(&stmt.condition);
         self.resolve_stmt(&stmt.then_branch);
@@ -61,19 +124,49 @@ impl<'a> Resolver<'a> {
     }
     fn visit_return_stmt(&mut self, stmt: &ReturnStmt) {
         if self.curr_function.is_none() {
-            LoxError::token_errro(
+            self.error(
                 &stmt.keyword,
                 "Can't return from top-level code.".to_string(),
             );
-            self.had_error = true;
         }
         if let Some(value) = &stmt.value {
+            if self.curr_function == Some(FunctionType::INITIALIZER) {
+                self.error(
+                    &stmt.keyword,
+                    "Can't return a value from an initializer.".to_string(),
+                );
+            }
             self.resolve_exper(value);
         }
     }
     fn visit_while_stmt(&mut self, stmt: &WhileStmt) {
         self.resolve_exper(&stmt.condition);
+
+        let enclosing_loop = self.in_loop;
+        self.in_loop = true;
+        self.resolve_stmt(&stmt.body);
+        self.in_loop = enclosing_loop;
+
+        if let Some(increment) = &stmt.increment {
+            self.resolve_exper(increment);
+        }
+    }
+    /// The loop variable lives in its own scope around the body, created fresh for every
+    /// iteration at runtime, while the iterable is resolved outside it.
+    fn visit_for_in_stmt(&mut self, stmt: &ForInStmt) {
+        self.resolve_exper(&stmt.iterable);
+        if let Some(range_end) = &stmt.range_end {
+            self.resolve_exper(range_end);
+        }
+
+        self.begin_scope();
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+        let enclosing_loop = self.in_loop;
+        self.in_loop = true;
         self.resolve_stmt(&stmt.body);
+        self.in_loop = enclosing_loop;
+        self.end_scope();
     }
     fn visit_var_stmt(&mut self, stmt: &VarStmt) {
         self.declare(&stmt.name);
@@ -103,6 +196,57 @@ impl<'a> Resolver<'a> {
             self.resolve_exper(argument);
         }
     }
+    fn visit_get_expr(&mut self, expr: &GetExpr) {
+        self.resolve_exper(&expr.object);
+    }
+    fn visit_set_expr(&mut self, expr: &SetExpr) {
+        self.resolve_exper(&expr.value);
+        self.resolve_exper(&expr.object);
+    }
+    fn visit_this_expr(&mut self, id: usize, expr: &ThisExpr) {
+        if self.curr_class.is_none() {
+            self.error(
+                &expr.keyword,
+                "Can't use 'this' outside of a class.".to_string(),
+            );
+            return;
+        }
+
+        self.resolve_local(
+            &Expr {
+                kind: ExprKind::This(expr.clone()),
+                id,
+            },
+            &expr.keyword,
+        );
+    }
+    fn visit_super_expr(&mut self, id: usize, expr: &SuperExpr) {
+        match self.curr_class {
+            None => {
+                self.error(
+                    &expr.keyword,
+                    "Can't use 'super' outside of a class.".to_string(),
+                );
+                return;
+            }
+            Some(ClassType::CLASS) => {
+                self.error(
+                    &expr.keyword,
+                    "Can't use 'super' in a class with no superclass.".to_string(),
+                );
+                return;
+            }
+            Some(ClassType::SUBCLASS) => {}
+        }
+
+        self.resolve_local(
+            &Expr {
+                kind: ExprKind::Super(expr.clone()),
+                id,
+            },
+            &expr.keyword,
+        );
+    }
     fn visit_grouping_expr(&mut self, expr: &GroupingExpr) {
         self.resolve_exper(&expr.expression);
     }
@@ -111,15 +255,75 @@ impl<'a> Resolver<'a> {
         self.resolve_exper(&expr.left);
         self.resolve_exper(&expr.right);
     }
+    fn visit_interpolation_expr(&mut self, expr: &InterpolationExpr) {
+        for part in &expr.parts {
+            self.resolve_exper(part);
+        }
+    }
+    fn visit_separator_expr(&mut self, expr: &SeparatorExpr) {
+        self.resolve_exper(&expr.left);
+        self.resolve_exper(&expr.right);
+    }
+    fn visit_ternary_expr(&mut self, expr: &TernaryExpr) {
+        self.resolve_exper(&expr.condition);
+        self.resolve_exper(&expr.left);
+        self.resolve_exper(&expr.right);
+    }
     fn visit_unary_expr(&mut self, expr: &UnaryExpr) {
         self.resolve_exper(&expr.right);
     }
+    fn visit_lambda_expr(&mut self, expr: &LambdaExpr) {
+        self.resolve_function(&expr.declaration, FunctionType::FUNCTION);
+    }
+    fn visit_map_expr(&mut self, expr: &MapExpr) {
+        for (key, value) in &expr.entries {
+            self.resolve_exper(key);
+            self.resolve_exper(value);
+        }
+    }
+    fn visit_list_expr(&mut self, expr: &ListExpr) {
+        for element in &expr.elements {
+            self.resolve_exper(element);
+        }
+    }
+    fn visit_index_expr(&mut self, expr: &IndexExpr) {
+        self.resolve_exper(&expr.object);
+        self.resolve_exper(&expr.index);
+    }
+    fn visit_index_set_expr(&mut self, expr: &IndexSetExpr) {
+        self.resolve_exper(&expr.object);
+        self.resolve_exper(&expr.index);
+        self.resolve_exper(&expr.value);
+    }
+    fn visit_compound_assign_expr(&mut self, expr: &CompoundAssignExpr) {
+        self.resolve_exper(&expr.target);
+        self.resolve_exper(&expr.value);
+    }
+    /// The target resolves like a plain read, and the write goes to the same slot.
+    fn visit_update_expr(&mut self, expr: &UpdateExpr) {
+        self.resolve_exper(&expr.target);
+    }
     pub fn resolve_stmts(&mut self, statements: &Vec<Stmt>) {
         for stmt in statements {
             self.resolve_stmt(stmt);
         }
     }
-    fn visit_break_stmt(&mut self) {}
+    fn visit_break_stmt(&mut self, stmt: &BreakStmt) {
+        if !self.in_loop {
+            self.error(
+                &stmt.keyword,
+                "Can't use 'break' outside of a loop.".to_string(),
+            );
+        }
+    }
+    fn visit_continue_stmt(&mut self, stmt: &ContinueStmt) {
+        if !self.in_loop {
+            self.error(
+                &stmt.keyword,
+                "Can't use 'continue' outside of a loop.".to_string(),
+            );
+        }
+    }
     fn resolve_stmt(&mut self, stmt: &Stmt) {
         match &stmt.expresstion {
             StmtExpr::Print(stmt) => return self.visit_print_stmt(stmt),
@@ -130,9 +334,12 @@ impl<'a> Resolver<'a> {
                 return self.visit_if_stmt(stmt);
             }
             StmtExpr::While(stmt) => return self.visit_while_stmt(stmt),
-            StmtExpr::Break => return self.visit_break_stmt(),
+            StmtExpr::ForIn(stmt) => self.visit_for_in_stmt(stmt),
+            StmtExpr::Break(break_stmt) => return self.visit_break_stmt(break_stmt),
+            StmtExpr::Continue(continue_stmt) => return self.visit_continue_stmt(continue_stmt),
             StmtExpr::Function(function_stmt) => return self.visit_function_stmt(function_stmt),
             StmtExpr::Return(return_stmt) => self.visit_return_stmt(return_stmt),
+            StmtExpr::Class(class_stmt) => self.visit_class_stmt(class_stmt),
         }
     }
     fn resolve_exper(&mut self, expr: &Expr) {
@@ -141,17 +348,36 @@ impl<'a> Resolver<'a> {
             ExprKind::Grouping(grouping_expr) => self.visit_grouping_expr(grouping_expr),
             ExprKind::Literal(literal_expr) => self.visit_literal_expr(literal_expr),
             ExprKind::Unary(unary_expr) => self.visit_unary_expr(unary_expr),
-            ExprKind::Separator(_) => todo!(),
-            ExprKind::Ternary(_) => todo!(),
+            ExprKind::Separator(separator_expr) => self.visit_separator_expr(separator_expr),
+            ExprKind::Ternary(ternary_expr) => self.visit_ternary_expr(ternary_expr),
             ExprKind::Variable(var_expr) => self.visit_variable_expr(expr.id, var_expr),
             ExprKind::Assgin(assessment_expr) => self.visit_assign_expr(expr.id, assessment_expr),
             ExprKind::Logical(logical_expr) => self.visit_logical_expr(logical_expr),
             ExprKind::Call(call_expr) => self.visit_call_expr(call_expr),
+            ExprKind::Get(get_expr) => self.visit_get_expr(get_expr),
+            ExprKind::Set(set_expr) => self.visit_set_expr(set_expr),
+            ExprKind::This(this_expr) => self.visit_this_expr(expr.id, this_expr),
+            ExprKind::Super(super_expr) => self.visit_super_expr(expr.id, super_expr),
+            ExprKind::CompoundAssign(compound_expr) => {
+                self.visit_compound_assign_expr(compound_expr)
+            }
+            ExprKind::Update(update_expr) => self.visit_update_expr(update_expr),
+            ExprKind::List(list_expr) => self.visit_list_expr(list_expr),
+            ExprKind::Lambda(lambda_expr) => self.visit_lambda_expr(lambda_expr),
+            ExprKind::Map(map_expr) => self.visit_map_expr(map_expr),
+            ExprKind::Index(index_expr) => self.visit_index_expr(index_expr),
+            ExprKind::IndexSet(index_set_expr) => self.visit_index_set_expr(index_set_expr),
+            ExprKind::Interpolation(interpolation_expr) => {
+                self.visit_interpolation_expr(interpolation_expr)
+            }
         }
     }
     fn resolve_function(&mut self, stmt: &FunctionStmt, f_type: FunctionType) {
         let enclosing_function = self.curr_function;
         self.curr_function = Some(f_type);
+        // A function body starts outside of any loop, even when declared inside one.
+        let enclosing_loop = self.in_loop;
+        self.in_loop = false;
 
         self.begin_scope();
         for param in &stmt.params {
@@ -161,24 +387,44 @@ impl<'a> Resolver<'a> {
         self.resolve_stmts(&stmt.body);
         self.end_scope();
         self.curr_function = enclosing_function;
+        self.in_loop = enclosing_loop;
+    }
+    fn error(&mut self, token: &Token, message: String) {
+        self.diagnostics
+            .push(Diagnostic::error_at_token(Phase::Resolve, token, message));
     }
     fn begin_scope(&mut self) {
         self.scopes.push(HashMap::new());
+        self.declarations.push(HashMap::new());
     }
     fn end_scope(&mut self) {
         self.scopes.pop();
+        self.declarations.pop();
     }
 
     fn declare(&mut self, name: &Token) {
         if let Some(scope) = self.scopes.last_mut() {
             if scope.contains_key(&name.lexeme) {
-                LoxError::token_errro(
-                    name,
-                    "Already a variable with this name in this scope.".to_string(),
-                );
-                self.had_error = true;
+                let message = "Already a variable with this name in this scope.".to_string();
+                let mut diagnostic = Diagnostic::error_at_token(Phase::Resolve, name, message);
+                if let Some(first) = self
+                    .declarations
+                    .last()
+                    .and_then(|declarations| declarations.get(&name.lexeme))
+                {
+                    diagnostic = diagnostic.with_secondary(Label::at_token(
+                        first,
+                        "variable first declared here".to_string(),
+                    ));
+                }
+                self.diagnostics.push(diagnostic);
             }
             scope.insert(name.lexeme.clone(), false);
+            if let Some(declarations) = self.declarations.last_mut() {
+                declarations
+                    .entry(name.lexeme.clone())
+                    .or_insert_with(|| name.clone());
+            }
         }
     }
     fn define(&mut self, name: &Token) {
@@ -192,11 +438,10 @@ impl<'a> Resolver<'a> {
             .last_mut()
             .is_some_and(|val| val.get(&expr.name.lexeme) == Some(&false))
         {
-            LoxError::token_errro(
+            self.error(
                 &expr.name,
                 "Can't read local variable in its own initializer.".to_string(),
             );
-            self.had_error = true;
         }
 
         self.resolve_local(
@@ -1,27 +1,41 @@
 use std::usize;
 
-use crate::error::LoxError;
-use crate::token::{parse_keyword, Token, TokenLiteral, TokenType};
+use unicode_ident::{is_xid_continue, is_xid_start};
 
+use crate::diagnostic::{Diagnostic, Diagnostics, Label, Phase, Severity};
+use crate::token::{parse_keyword, Span, Token, TokenLiteral, TokenType};
+
+/// Scans source text into tokens. `start` and `curr` are byte offsets that always sit on a
+/// UTF-8 character boundary, so every step is constant time and lexemes can be sliced directly.
 pub struct Scanner {
     source: String,
     tokens: Vec<Token>,
     start: usize,
     curr: usize,
     line: usize,
+    // Column of `curr` in characters, kept up to date as the cursor moves.
+    column: usize,
+    start_line: usize,
+    start_column: usize,
+    // One entry per `${` still open, counting the plain `{` nested inside it.
+    interpolations: Vec<usize>,
 
-    pub is_error: bool,
+    pub diagnostics: Diagnostics,
 }
 
 impl Scanner {
     pub fn new(source: String) -> Self {
         Scanner {
             source,
-            is_error: false,
+            diagnostics: Diagnostics::new(),
             tokens: vec![],
             start: 0,
             curr: 0,
             line: 1,
+            column: 1,
+            start_line: 1,
+            start_column: 1,
+            interpolations: vec![],
         }
     }
     fn is_at_end(&self) -> bool {
@@ -30,6 +44,8 @@ impl Scanner {
     pub fn scan_tokens(&mut self) -> Vec<Token> {
         while !self.is_at_end() {
             self.start = self.curr;
+            self.start_line = self.line;
+            self.start_column = self.column;
             self.scan_token();
         }
 
@@ -38,20 +54,48 @@ impl Scanner {
             token_type: TokenType::EOF,
             literal: None,
             line: self.line,
+            span: Span {
+                start: self.curr,
+                end: self.curr,
+                column: self.column,
+            },
         });
         self.tokens.clone()
     }
-    fn get_char_from_source(&self, idx: usize) -> char {
-        if self.is_at_end() {
-            return '\0';
-        };
-        return self.source.chars().nth(idx).unwrap();
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.column = 1;
+    }
+    fn error(&mut self, message: &str) {
+        self.error_at(
+            self.start,
+            self.curr,
+            self.start_line,
+            self.start_column,
+            message,
+        );
+    }
+    /// Reports an error underlining the source between byte offsets `start` and `end`.
+    fn error_at(&mut self, start: usize, end: usize, line: usize, column: usize, message: &str) {
+        let length = self
+            .sub_string(Some(start), Some(end))
+            .lines()
+            .next()
+            .unwrap_or("")
+            .chars()
+            .count();
+        self.diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            Phase::Scan,
+            message.to_string(),
+            "".to_string(),
+            Label::new(line, column, length, "".to_string()),
+        ));
     }
-
     fn advance(&mut self) -> char {
-        let c = self.get_char_from_source(self.curr as usize);
-
-        self.curr += 1;
+        let c = self.peek();
+        self.curr += c.len_utf8();
+        self.column += 1;
 
         c
     }
@@ -62,14 +106,85 @@ impl Scanner {
         match c {
             '(' => self.add_token(TokenType::LEFTPAREN, None),
             ')' => self.add_token(TokenType::RIGHTPAREN, None),
-            '{' => self.add_token(TokenType::LEFTBRACE, None),
-            '}' => self.add_token(TokenType::RIGHTBRACE, None),
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LEFTBRACE, None)
+            }
+            '}' => match self.interpolations.last_mut() {
+                // This closes a `${`, so carry on with the rest of the string.
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.add_token(TokenType::RIGHTBRACE, None);
+                    self.start = self.curr;
+                    self.start_line = self.line;
+                    self.start_column = self.column;
+                    self.string_to_end();
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RIGHTBRACE, None)
+                }
+                None => self.add_token(TokenType::RIGHTBRACE, None),
+            },
+            '[' => self.add_token(TokenType::LEFTBRACKET, None),
+            ']' => self.add_token(TokenType::RIGHTBRACKET, None),
             ',' => self.add_token(TokenType::COMMA, None),
-            '.' => self.add_token(TokenType::DOT, None),
-            '-' => self.add_token(TokenType::MINUS, None),
-            '+' => self.add_token(TokenType::PLUS, None),
+            '.' => {
+                if self.match_char('.') {
+                    self.add_token(TokenType::DOTDOT, None)
+                } else {
+                    self.add_token(TokenType::DOT, None)
+                }
+            }
+            '-' => {
+                let mut curr_type = TokenType::MINUS;
+                if self.match_char('-') {
+                    curr_type = TokenType::MINUSMINUS;
+                } else if self.match_char('=') {
+                    curr_type = TokenType::MINUSEQUAL;
+                }
+                self.add_token(curr_type, None);
+            }
+            '+' => {
+                let mut curr_type = TokenType::PLUS;
+                if self.match_char('+') {
+                    curr_type = TokenType::PLUSPLUS;
+                } else if self.match_char('=') {
+                    curr_type = TokenType::PLUSEQUAL;
+                }
+                self.add_token(curr_type, None);
+            }
             ';' => self.add_token(TokenType::SEMICOLON, None),
-            '*' => self.add_token(TokenType::STAR, None),
+            '*' => {
+                let mut curr_type = TokenType::STAR;
+                if self.match_char('*') {
+                    curr_type = TokenType::STARSTAR;
+                } else if self.match_char('=') {
+                    curr_type = TokenType::STAREQUAL;
+                }
+                self.add_token(curr_type, None);
+            }
+            '%' => {
+                let mut curr_type = TokenType::PERCENT;
+                if self.match_char('=') {
+                    curr_type = TokenType::PERCENTEQUAL;
+                }
+                self.add_token(curr_type, None);
+            }
+            '&' => self.add_token(TokenType::AMPERSAND, None),
+            '|' => self.add_token(TokenType::PIPE, None),
+            '^' => self.add_token(TokenType::CARET, None),
+            '~' => {
+                let mut curr_type = TokenType::TILDE;
+                // `~/*` and `~//` are a tilde followed by a comment.
+                if self.peek() == '/' && !matches!(self.peek_next(), '*' | '/') {
+                    self.advance();
+                    curr_type = TokenType::TILDESLASH;
+                }
+                self.add_token(curr_type, None);
+            }
             '!' => {
                 let mut curr_type = TokenType::BANG;
                 match self.match_char('=') {
@@ -80,44 +195,42 @@ impl Scanner {
             }
             '=' => {
                 let mut curr_type = TokenType::EQUAL;
-                match self.match_char('=') {
-                    true => curr_type = TokenType::EQUALEQUAL,
-                    _ => {}
+                if self.match_char('=') {
+                    curr_type = TokenType::EQUALEQUAL;
+                } else if self.match_char('>') {
+                    curr_type = TokenType::ARROW;
                 }
                 self.add_token(curr_type, None);
             }
 
             '<' => {
                 let mut curr_type = TokenType::LESS;
-                match self.match_char('=') {
-                    true => curr_type = TokenType::LESSEQUAL,
-                    _ => {}
+                if self.match_char('=') {
+                    curr_type = TokenType::LESSEQUAL;
+                } else if self.match_char('<') {
+                    curr_type = TokenType::LESSLESS;
                 }
                 self.add_token(curr_type, None);
             }
 
             '>' => {
                 let mut curr_type = TokenType::GREATER;
-                match self.match_char('=') {
-                    true => curr_type = TokenType::GREATEREQUAL,
-                    _ => {}
+                if self.match_char('=') {
+                    curr_type = TokenType::GREATEREQUAL;
+                } else if self.match_char('>') {
+                    curr_type = TokenType::GREATERGREATER;
                 }
                 self.add_token(curr_type, None);
             }
             '/' => {
-                let next = self.peek();
-                if next == '/' {
+                if self.match_char('/') {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
-                } else if next == '*' {
-                    while self.peek() != '/' && !self.is_at_end() {
-                        if self.peek() == '\n' {
-                            self.line += 1;
-                        }
-                        self.advance();
-                    }
-                    self.advance();
+                } else if self.match_char('*') {
+                    self.block_comment();
+                } else if self.match_char('=') {
+                    self.add_token(TokenType::SLASHEQUAL, None);
                 } else {
                     self.add_token(TokenType::SLASH, None);
                 }
@@ -128,20 +241,50 @@ impl Scanner {
             ' ' => {}
             '\r' => {}
             '\t' => {}
-            '\n' => self.line += 1,
-            '"' => self.string_to_end(),
+            '\n' => self.new_line(),
+            '"' => {
+                if self.peek() == '"' && self.peek_next() == '"' {
+                    self.advance();
+                    self.advance();
+                    self.raw_string_to_end();
+                } else {
+                    self.string_to_end();
+                }
+            }
             _ => {
-                if c.is_numeric() {
+                if self.is_digit(c) {
                     self.number_to_end();
                 } else if self.is_alph(c) {
                     self.identifier_to_end();
                 } else {
-                    self.is_error = true;
-                    LoxError::error(self.line, "Unexpected character.".to_string());
+                    self.error("Unexpected character.");
                 }
             }
         }
     }
+    /// Skips a `/* ... */` comment, which may contain nested block comments.
+    fn block_comment(&mut self) {
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                // Point at the opening `/*` rather than at the end of the file.
+                self.error_at(
+                    self.start,
+                    self.start + 2,
+                    self.start_line,
+                    self.start_column,
+                    "Unterminated block comment.",
+                );
+                return;
+            }
+            match self.advance() {
+                '/' if self.match_char('*') => depth += 1,
+                '*' if self.match_char('/') => depth -= 1,
+                '\n' => self.new_line(),
+                _ => {}
+            }
+        }
+    }
     fn identifier_to_end(&mut self) {
         while self.is_alph_numeric(self.peek()) {
             self.advance();
@@ -155,58 +298,228 @@ impl Scanner {
         self.add_token(curr_type.unwrap(), None);
     }
     fn string_to_end(&mut self) {
+        let mut value = String::new();
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+            match self.advance() {
+                '\\' => self.escape(&mut value),
+                '$' if self.peek() == '{' => {
+                    self.advance();
+                    self.interpolations.push(0);
+                    self.add_token(TokenType::INTERPOLATION, Some(TokenLiteral::Text(value)));
+                    return;
+                }
+                '\n' => {
+                    self.new_line();
+                    value.push('\n');
+                }
+                c => value.push(c),
             }
-            self.advance();
         }
-        if self.is_at_end() && self.get_char_from_source(self.curr as usize) != '"' {
-            self.is_error = true;
-            LoxError::error(self.line, "Unterminated string.".to_string());
+        if self.is_at_end() {
+            self.error("Unterminated string.");
             return;
         }
 
         self.advance();
-        let curr_str = self.sub_string(Some(self.start as usize + 1), Some(self.curr as usize - 1));
-
-        self.add_token(TokenType::STRING, Some(TokenLiteral::Text(curr_str)));
+        self.add_token(TokenType::STRING, Some(TokenLiteral::Text(value)));
     }
+    /// Decodes the escape after a `\` into `value`. Invalid escapes are reported where they
+    /// appear and left out of the string so scanning can carry on.
+    fn escape(&mut self, value: &mut String) {
+        // The backslash has already been consumed and is one byte / one column wide.
+        let (start, line, column) = (self.curr - 1, self.line, self.column - 1);
+        if self.is_at_end() {
+            return;
+        }
+        match self.advance() {
+            'n' => value.push('\n'),
+            't' => value.push('\t'),
+            '\\' => value.push('\\'),
+            '"' => value.push('"'),
+            '$' => value.push('$'),
+            'u' => {
+                if !self.match_char('{') {
+                    self.error_at(start, self.curr, line, column, "Expect '{' after '\\u'.");
+                    return;
+                }
+                let digits_start = self.curr;
+                while self.peek().is_ascii_hexdigit() {
+                    self.advance();
+                }
+                let digits = self.sub_string(Some(digits_start), Some(self.curr));
+                if !self.match_char('}') {
+                    self.error_at(
+                        start,
+                        self.curr,
+                        line,
+                        column,
+                        "Expect '}' after unicode escape.",
+                    );
+                    return;
+                }
+                let code = match digits.len() {
+                    1..=6 => u32::from_str_radix(&digits, 16).ok(),
+                    _ => None,
+                };
+                match code.and_then(char::from_u32) {
+                    Some(c) => value.push(c),
+                    None => {
+                        self.error_at(start, self.curr, line, column, "Invalid unicode escape.")
+                    }
+                }
+            }
+            c => {
+                if c == '\n' {
+                    self.new_line();
+                }
+                self.error_at(
+                    start,
+                    self.curr,
+                    line,
+                    column,
+                    &format!("Invalid escape sequence '\\{}'.", c.escape_default()),
+                );
+            }
+        }
+    }
+    /// A `"""`-delimited string: taken verbatim, with no escapes, and free to span lines.
+    fn raw_string_to_end(&mut self) {
+        while !self.is_at_end() && !self.source[self.curr..].starts_with("\"\"\"") {
+            if self.advance() == '\n' {
+                self.new_line();
+            }
+        }
+        if self.is_at_end() {
+            self.error("Unterminated raw string.");
+            return;
+        }
 
-    fn number_to_end(&mut self) {
-        while self.is_digit(self.peek()) {
+        let value = self.sub_string(Some(self.start + 3), Some(self.curr));
+        for _ in 0..3 {
             self.advance();
         }
+        self.add_token(TokenType::STRING, Some(TokenLiteral::Text(value)));
+    }
+
+    fn number_to_end(&mut self) {
+        let radix = match (self.source[self.start..].starts_with('0'), self.peek()) {
+            (true, 'x' | 'X') => 16,
+            (true, 'b' | 'B') => 2,
+            (true, 'o' | 'O') => 8,
+            _ => 10,
+        };
+        let value = if radix == 10 {
+            self.decimal_number()
+        } else {
+            self.radix_number(radix)
+        };
+
+        // Still produce a NUMBER so the parser doesn't report the same mistake again.
+        let value = value.unwrap_or_else(|message| {
+            self.error(&message);
+            0.0
+        });
+        self.add_token(TokenType::NUMBER, Some(TokenLiteral::Float(value)));
+    }
+    /// `12_345.6e-7`: digits with an optional fraction and exponent.
+    fn decimal_number(&mut self) -> Result<f64, String> {
+        self.separated_digits();
 
         if self.peek() == '.' && self.is_digit(self.peek_next()) {
             self.advance();
-            while self.is_digit(self.peek()) {
+            self.separated_digits();
+        }
+        if matches!(self.peek(), 'e' | 'E') {
+            self.advance();
+            if matches!(self.peek(), '+' | '-') {
                 self.advance();
             }
+            if !self.is_digit(self.peek()) {
+                return Err("Expect digits in exponent.".to_string());
+            }
+            self.separated_digits();
         }
-        let num = self.sub_string(Some(self.start), Some(self.curr));
-        self.add_token(
-            TokenType::NUMBER,
-            Some(TokenLiteral::Float(num.parse().unwrap())),
-        );
+
+        let suffix_start = self.curr;
+        while self.is_alph_numeric(self.peek()) {
+            self.advance();
+        }
+        if self.curr > suffix_start {
+            let suffix = self.sub_string(Some(suffix_start), Some(self.curr));
+            return Err(format!("Invalid suffix '{}' on number literal.", suffix));
+        }
+
+        let text = self.sub_string(Some(self.start), Some(self.curr));
+        self.check_separators(&text, 10)?;
+        text.replace('_', "")
+            .parse()
+            .map_err(|_| "Invalid number literal.".to_string())
     }
+    /// `0xFF`, `0b1010` and `0o755`, with the prefix letter still to be consumed.
+    fn radix_number(&mut self, radix: u32) -> Result<f64, String> {
+        self.advance();
+        let digits_start = self.curr;
+        while self.is_alph_numeric(self.peek()) {
+            self.advance();
+        }
 
-    fn peek_next(&self) -> char {
-        if self.curr + 1 >= self.source.len() {
-            return '\0';
+        let prefix = self.sub_string(Some(self.start), Some(digits_start));
+        let digits = self.sub_string(Some(digits_start), Some(self.curr));
+        let name = match radix {
+            16 => "hexadecimal",
+            8 => "octal",
+            _ => "binary",
+        };
+        if digits.is_empty() {
+            return Err(format!("Expect digits after '{}'.", prefix));
+        }
+        if let Some(c) = digits.chars().find(|c| *c != '_' && !c.is_digit(radix)) {
+            return Err(format!("Invalid digit '{}' in {} literal.", c, name));
         }
-        self.get_char_from_source(self.curr + 1)
+        self.check_separators(&digits, radix)?;
+        u64::from_str_radix(&digits.replace('_', ""), radix)
+            .map(|value| value as f64)
+            .map_err(|_| format!("The {} literal is too large.", name))
+    }
+    fn separated_digits(&mut self) {
+        while self.is_digit(self.peek()) || self.peek() == '_' {
+            self.advance();
+        }
+    }
+    /// Every `_` has to sit between two digits, so `1__0`, `1_` and `0x_F` are rejected.
+    fn check_separators(&self, text: &str, radix: u32) -> Result<(), String> {
+        let chars: Vec<char> = text.chars().collect();
+        for (i, c) in chars.iter().enumerate() {
+            if *c != '_' {
+                continue;
+            }
+            let before = i.checked_sub(1).and_then(|i| chars.get(i));
+            let after = chars.get(i + 1);
+            if !before.is_some_and(|c| c.is_digit(radix))
+                || !after.is_some_and(|c| c.is_digit(radix))
+            {
+                return Err("A '_' separator must be between two digits.".to_string());
+            }
+        }
+        Ok(())
     }
 
+    fn peek_next(&self) -> char {
+        let mut chars = self.source[self.curr..].chars();
+        chars.next();
+        chars.next().unwrap_or('\0')
+    }
+
+    // Identifiers follow Unicode XID_Start / XID_Continue, plus a leading `_`.
     fn is_alph_numeric(&self, c: char) -> bool {
-        self.is_digit(c) || self.is_alph(c)
+        is_xid_continue(c)
     }
 
     fn is_digit(&self, c: char) -> bool {
         c >= '0' && c <= '9'
     }
     fn is_alph(&self, c: char) -> bool {
-        (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || c == '_'
+        c == '_' || is_xid_start(c)
     }
 
     fn sub_string(&self, start: Option<usize>, end: Option<usize>) -> String {
@@ -216,10 +529,7 @@ impl Scanner {
         self.source[self.start as usize..self.curr as usize].to_string()
     }
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            return '\0';
-        };
-        self.get_char_from_source(self.curr as usize)
+        self.source[self.curr..].chars().next().unwrap_or('\0')
     }
     fn add_token(&mut self, token_type: TokenType, literal: Option<TokenLiteral>) {
         let text = self.sub_string(Some(self.start), Some(self.curr));
@@ -228,17 +538,22 @@ impl Scanner {
             lexeme: text,
             token_type,
             literal,
-            line: self.line,
+            line: self.start_line,
+            span: Span {
+                start: self.start,
+                end: self.curr,
+                column: self.start_column,
+            },
         });
     }
     fn match_char(&mut self, expected: char) -> bool {
         if self.is_at_end() {
             return false;
         }
-        if self.get_char_from_source(self.curr) != expected {
+        if self.peek() != expected {
             return false;
         }
-        self.curr += 1;
+        self.advance();
         return true;
     }
 }
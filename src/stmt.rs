@@ -17,15 +17,41 @@ pub enum StmtExpr {
     Block(BlockStmt),
     If(IfStmt),
     While(WhileStmt),
-    Break,
+    ForIn(ForInStmt),
+    Break(BreakStmt),
+    Continue(ContinueStmt),
     Function(FunctionStmt),
     Return(ReturnStmt),
+    Class(ClassStmt),
 }
 
 #[derive(Debug, Clone)]
 pub struct WhileStmt {
     pub condition: Expr,
     pub body: Box<Stmt>,
+    /// The increment clause of a desugared `for` loop, run after every iteration.
+    pub increment: Option<Expr>,
+}
+
+/// `for (var name in iterable) body`, or `for (var name in iterable..range_end) body` for a
+/// numeric range. `keyword` is the `in`, used to report values that can't be iterated.
+#[derive(Debug, Clone)]
+pub struct ForInStmt {
+    pub name: Token,
+    pub keyword: Token,
+    pub iterable: Expr,
+    pub range_end: Option<Expr>,
+    pub body: Box<Stmt>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BreakStmt {
+    pub keyword: Token,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContinueStmt {
+    pub keyword: Token,
 }
 
 #[derive(Debug, Clone)]
@@ -63,6 +89,13 @@ pub struct FunctionStmt {
     pub body: Vec<Stmt>,
 }
 
+#[derive(Debug, Clone)]
+pub struct ClassStmt {
+    pub name: Token,
+    pub superclass: Option<Expr>,
+    pub methods: Vec<FunctionStmt>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ReturnStmt {
     pub keyword: Token,
@@ -108,17 +141,40 @@ impl Stmt {
         }
     }
 
-    pub fn while_stmt(condition: Expr, body: Stmt) -> Self {
+    pub fn while_stmt(condition: Expr, body: Stmt, increment: Option<Expr>) -> Self {
         Self {
             expresstion: StmtExpr::While(WhileStmt {
                 condition,
                 body: Box::new(body),
+                increment,
+            }),
+        }
+    }
+    pub fn for_in_stmt(
+        name: Token,
+        keyword: Token,
+        iterable: Expr,
+        range_end: Option<Expr>,
+        body: Stmt,
+    ) -> Self {
+        Self {
+            expresstion: StmtExpr::ForIn(ForInStmt {
+                name,
+                keyword,
+                iterable,
+                range_end,
+                body: Box::new(body),
             }),
         }
     }
-    pub fn break_stmt() -> Self {
+    pub fn break_stmt(keyword: Token) -> Self {
         Self {
-            expresstion: StmtExpr::Break,
+            expresstion: StmtExpr::Break(BreakStmt { keyword }),
+        }
+    }
+    pub fn continue_stmt(keyword: Token) -> Self {
+        Self {
+            expresstion: StmtExpr::Continue(ContinueStmt { keyword }),
         }
     }
 
@@ -133,6 +189,16 @@ impl Stmt {
             expresstion: StmtExpr::Return(ReturnStmt { keyword, value }),
         }
     }
+
+    pub fn class_stmt(name: Token, superclass: Option<Expr>, methods: Vec<FunctionStmt>) -> Self {
+        Self {
+            expresstion: StmtExpr::Class(ClassStmt {
+                name,
+                superclass,
+                methods,
+            }),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -12,13 +12,21 @@ pub enum TokenType {
     RIGHTPAREN,
     LEFTBRACE,
     RIGHTBRACE,
+    LEFTBRACKET,
+    RIGHTBRACKET,
     COMMA,
     DOT,
+    DOTDOT,
     MINUS,
     PLUS,
     SEMICOLON,
     SLASH,
     STAR,
+    PERCENT,
+    AMPERSAND,
+    PIPE,
+    CARET,
+    TILDE,
 
     //Ternary
     QUESTION,
@@ -29,14 +37,31 @@ pub enum TokenType {
     BANGEQUAL,
     EQUAL,
     EQUALEQUAL,
+    // `=>`, between the parameters and body of an arrow function.
+    ARROW,
     GREATER,
     GREATEREQUAL,
     LESS,
     LESSEQUAL,
+    STARSTAR,
+    // `~/`, integer division. `//` already starts a comment.
+    TILDESLASH,
+    LESSLESS,
+    GREATERGREATER,
+    PLUSEQUAL,
+    MINUSEQUAL,
+    STAREQUAL,
+    SLASHEQUAL,
+    PERCENTEQUAL,
+    PLUSPLUS,
+    MINUSMINUS,
 
     // Literals.
     IDENTIFIER,
     STRING,
+    // A string fragment that is followed by an interpolated `${...}` expression. The `}`
+    // closing the expression is its own RIGHTBRACE, then the rest of the string follows.
+    INTERPOLATION,
     NUMBER,
 
     // Keywords.
@@ -57,6 +82,7 @@ pub enum TokenType {
     VAR,
     WHILE,
     BREAK,
+    CONTINUE,
 
     EOF,
 }
@@ -79,7 +105,8 @@ static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "true"   => TokenType::TRUE,
     "var"    => TokenType::VAR,
     "while"  => TokenType::WHILE,
-    "break" => TokenType::BREAK
+    "break" => TokenType::BREAK,
+    "continue" => TokenType::CONTINUE
 };
 pub fn parse_keyword(keyword: &str) -> Option<TokenType> {
     KEYWORDS.get(keyword).cloned()
@@ -114,11 +141,21 @@ impl Display for TokenLiteral {
 //     }
 // }
 
+/// Where a token sits in the source: the byte offsets `start..end` and the 1-based column
+/// (counted in characters) of `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub column: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub lexeme: String,
     pub literal: Option<TokenLiteral>,
     pub line: usize,
+    pub span: Span,
     pub token_type: TokenType,
 }
 
@@ -134,12 +171,14 @@ impl Token {
         lexeme: String,
         literal: Option<TokenLiteral>,
         line: usize,
+        span: Span,
     ) -> Self {
         Token {
             lexeme,
             token_type,
             literal,
             line,
+            span,
         }
     }
     pub fn to_string(self) -> String {
@@ -0,0 +1,833 @@
+use std::{
+    env, fs,
+    process::{Command, Output},
+};
+
+fn execute(name: &str, source: &str) -> Output {
+    execute_with_args(name, &[], source)
+}
+
+fn execute_with_args(name: &str, args: &[&str], source: &str) -> Output {
+    let path = env::temp_dir().join(format!("lox_interpreter_test_{name}.lox"));
+    fs::write(&path, source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_lox-lang"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    output
+}
+
+fn run(name: &str, source: &str) -> String {
+    String::from_utf8(execute(name, source).stdout).unwrap()
+}
+
+/// Runs each source as its own script and checks that it fails with the given error first.
+fn assert_errors(name: &str, cases: &[(&str, &str)]) {
+    for (i, (source, message)) in cases.iter().enumerate() {
+        let output = execute(&format!("{name}_{i}"), source);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            matches!(output.status.code(), Some(65 | 70)),
+            "running {}: exited with {:?}",
+            source,
+            output.status.code()
+        );
+        assert!(
+            stdout.starts_with(message),
+            "running {}: {}",
+            source,
+            stdout
+        );
+    }
+}
+
+#[test]
+fn class_fields_methods_and_initializer() {
+    let output = run(
+        "class_fields_methods_and_initializer",
+        r#"
+        class Point {
+          init(x, y) {
+            this.x = x;
+            this.y = y;
+          }
+          sum() { return this.x + this.y; }
+        }
+        var p = Point(1, 2);
+        print p.sum();
+        var sum = p.sum;
+        p.x = 10;
+        print sum();
+        print p;
+        print p.init(3, 4).x;
+        "#,
+    );
+    assert_eq!(output, "3\n12\nPoint instance\n3\n");
+}
+
+#[test]
+fn this_outside_class_is_rejected() {
+    let output = run(
+        "this_outside_class_is_rejected",
+        "fun f() { return this; }",
+    );
+    assert!(output.contains("Can't use 'this' outside of a class."));
+}
+
+#[test]
+fn inheritance_and_super_calls() {
+    let output = run(
+        "inheritance_and_super_calls",
+        r#"
+        class A {
+          init(n) { this.n = n; }
+          describe() { return "A " + this.n; }
+          base() { return "base"; }
+        }
+        class B < A {
+          init(n) { super.init(n * 2); }
+          describe() { return "B then " + super.describe(); }
+        }
+        class C < B {}
+        var c = C(5);
+        print c.describe();
+        print c.base();
+        "#,
+    );
+    assert_eq!(output, "B then A 10\nbase\n");
+}
+
+#[test]
+fn invalid_super_usage_is_rejected() {
+    let output = run("class_inheriting_itself", "class A < A {}");
+    assert!(output.contains("A class can't inherit from itself."));
+
+    let output = run("super_without_superclass", "class A { f() { super.f(); } }");
+    assert!(output.contains("Can't use 'super' in a class with no superclass."));
+}
+
+#[test]
+fn ternary_only_evaluates_chosen_branch() {
+    let output = run(
+        "ternary_only_evaluates_chosen_branch",
+        r#"
+        fun loud(value) { print "evaluated " + value; return value; }
+        print true ? loud("yes") : loud("no");
+        var n = 0;
+        print n > 0 ? "positive" : n < 0 ? "negative" : "zero";
+        "#,
+    );
+    assert_eq!(output, "evaluated yes\nyes\nzero\n");
+}
+
+#[test]
+fn separator_in_for_increment() {
+    let output = run(
+        "separator_in_for_increment",
+        r#"
+        var j = 3;
+        for (var i = 0; i < j; i = i + 1, j = j - 1) {
+          print i + j;
+        }
+        print (1, 2);
+        "#,
+    );
+    assert_eq!(output, "3\n3\n2\n");
+}
+
+#[test]
+fn continue_runs_for_increment() {
+    let output = run(
+        "continue_runs_for_increment",
+        r#"
+        for (var i = 0; i < 6; i = i + 1) {
+          if (i == 1 or i == 3) continue;
+          if (i == 5) break;
+          print i;
+        }
+        var j = 0;
+        while (j < 3) {
+          j = j + 1;
+          if (j == 2) continue;
+          print j;
+        }
+        "#,
+    );
+    assert_eq!(output, "0\n2\n4\n1\n3\n");
+}
+
+#[test]
+fn loop_control_outside_loop_is_rejected() {
+    let output = run("break_outside_loop", "break;");
+    assert!(output.contains("Can't use 'break' outside of a loop."));
+
+    let output = run(
+        "continue_inside_function_in_loop",
+        "while (true) { fun f() { continue; } }",
+    );
+    assert!(output.contains("Can't use 'continue' outside of a loop."));
+}
+
+#[test]
+fn runtime_error_is_reported_and_halts() {
+    let output = execute(
+        "runtime_error_is_reported_and_halts",
+        "print 1;\nprint 1 / 0;\nprint 2;",
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(70));
+    assert!(stdout.starts_with("1\nerror at '/': Can't divide by Zero\n --> line 2, column 9\n"));
+    assert!(!stdout.contains("\n2\n"));
+}
+
+#[test]
+fn compile_error_exits_with_65() {
+    let output = execute("compile_error_exits_with_65", "print (;");
+    assert_eq!(output.status.code(), Some(65));
+}
+
+#[test]
+fn runtime_error_prints_call_stack() {
+    let output = run(
+        "runtime_error_prints_call_stack",
+        "fun inner(n) {\n  return n / 0;\n}\nfun outer() {\n  return inner(1);\n}\nprint outer();\n",
+    );
+    assert_eq!(
+        output,
+        "error at '/': Can't divide by Zero
+ --> line 2, column 12
+  |
+2 |   return n / 0;
+  |            ^
+    in inner() [line 5]
+    in outer() [line 7]
+"
+    );
+}
+
+#[test]
+fn unbounded_recursion_is_a_runtime_error() {
+    let output = execute(
+        "unbounded_recursion_is_a_runtime_error",
+        "fun f() { f(); }\nf();",
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(70));
+    assert!(stdout.starts_with("error at ')': Stack overflow.\n --> line 1, column 13\n"));
+    assert!(stdout.contains("previous frame repeated 998 more times"));
+}
+
+#[test]
+fn host_can_lower_max_call_depth() {
+    let mut lox = lox_lang::lox::Lox::new();
+    lox.interpretor.set_max_call_depth(10);
+
+    let diagnostics = lox.run("fun down(n) { if (n > 0) down(n - 1); }\ndown(9);");
+    assert!(diagnostics.is_empty());
+
+    let diagnostics = lox.run("down(10);");
+    assert!(lox.had_runtime_error);
+    let error = diagnostics.iter().next().unwrap();
+    assert_eq!(error.message, "Stack overflow.");
+    assert_eq!(error.notes.len(), 2);
+}
+
+#[test]
+fn resolved_locals_survive_later_runs() {
+    let mut lox = lox_lang::lox::Lox::new();
+
+    assert!(lox.run("fun g(n) { return n; }").is_empty());
+    assert!(lox.run("{ var a; { print a; } }").is_empty());
+    let diagnostics = lox.run("print g(7);");
+    assert!(diagnostics.is_empty());
+    assert!(!lox.had_runtime_error);
+}
+
+#[test]
+fn embedded_interpreter_reaches_the_default_depth_on_a_test_thread() {
+    // Test threads only get a 2 MB stack, far less than the binary's main thread.
+    let depth = lox_lang::interpreter::DEFAULT_MAX_CALL_DEPTH;
+    let mut lox = lox_lang::lox::Lox::new();
+
+    let diagnostics = lox.run(&format!(
+        "fun g(n) {{ if (n > 0) return 1 + g(n - 1); return 0; }}\nvar r = g({});",
+        depth - 1
+    ));
+    assert!(diagnostics.is_empty());
+
+    let diagnostics = lox.run(&format!("g({});", depth));
+    let error = diagnostics.iter().next().unwrap();
+    assert_eq!(error.message, "Stack overflow.");
+}
+
+#[test]
+fn redeclaration_points_at_first_declaration() {
+    let output = run(
+        "redeclaration_points_at_first_declaration",
+        "{\n  var a = 1;\n  var a = 2;\n}\n",
+    );
+    assert_eq!(
+        output,
+        "error at 'a': Already a variable with this name in this scope.
+ --> line 3, column 7
+  |
+2 |   var a = 1;
+  |       - variable first declared here
+3 |   var a = 2;
+  |       ^
+"
+    );
+}
+
+#[test]
+fn run_returns_diagnostics_instead_of_printing() {
+    let mut lox = lox_lang::lox::Lox::new();
+
+    let diagnostics = lox.run("var a = 1;\nprint a +;");
+    assert!(lox.had_error);
+    assert!(diagnostics.has_errors());
+    assert_eq!(diagnostics.len(), 1);
+
+    let error = diagnostics.iter().next().unwrap();
+    assert_eq!(error.message, "Expect expression.");
+    assert_eq!(error.location, "at ';'");
+    assert_eq!((error.primary.line, error.primary.column), (2, 10));
+}
+
+#[test]
+fn hosts_can_filter_diagnostics_by_severity_and_phase() {
+    use lox_lang::diagnostic::{Diagnostic, Phase, Severity};
+
+    let mut lox = lox_lang::lox::Lox::new();
+    let diagnostics = lox.run("var a = ;\nprint 1 +;\n{ var b = 1; var b = 2; }");
+    let parse_errors: Vec<&Diagnostic> = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error && d.phase == Phase::Parse)
+        .collect();
+    assert_eq!(parse_errors.len(), 2);
+    assert_eq!(parse_errors[1].primary.line, 2);
+
+    let diagnostics = lox.run("{ var b = 1; var b = 2; }");
+    assert!(diagnostics.iter().all(|d| d.phase == Phase::Resolve));
+
+    let diagnostics = lox.run("print -nil;");
+    let runtime: Vec<&str> = diagnostics
+        .iter()
+        .filter(|d| d.phase == Phase::Runtime)
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(runtime, vec!["Expected a number"]);
+}
+
+#[test]
+fn json_diagnostics_are_written_one_per_line() {
+    let output = execute_with_args(
+        "json_diagnostics_are_written_one_per_line",
+        &["--json-diagnostics"],
+        "print \"a\";\nprint -\"b\";",
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(70));
+    assert_eq!(stdout, "a\n");
+    assert!(stderr.ends_with(
+        r#","line":2,"column":7,"severity":"error","phase":"runtime","message":"Expected a number","notes":[],"related":null}
+"#
+    ));
+    assert!(stderr.starts_with(r#"{"file":""#));
+    assert_eq!(stderr.lines().count(), 1);
+}
+
+#[test]
+fn json_diagnostics_report_the_phase() {
+    let output = execute_with_args(
+        "json_diagnostics_report_the_phase",
+        &["--json-diagnostics"],
+        "print @;",
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(65));
+    assert!(stderr.contains(
+        r#""line":1,"column":7,"severity":"error","phase":"scan","message":"Unexpected character.""#
+    ));
+    assert!(stderr.contains(r#""phase":"parse","message":"Expect expression.""#));
+}
+
+#[test]
+fn json_diagnostics_keep_notes_and_related_labels() {
+    let output = execute_with_args(
+        "json_diagnostics_keep_notes_and_related_labels",
+        &["--json-diagnostics"],
+        "fun inner() {\n  return -nil;\n}\nfun outer() { inner(); }\nouter();",
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.ends_with(
+        r#""message":"Expected a number","notes":["in inner() [line 4]","in outer() [line 5]"],"related":null}
+"#
+    ));
+
+    let output = execute_with_args(
+        "json_diagnostics_keep_notes_and_related_labels",
+        &["--json-diagnostics"],
+        "{\n  var a = 1;\n  var a = 2;\n}",
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.ends_with(
+        r#""notes":[],"related":{"line":2,"column":7,"message":"variable first declared here"}}
+"#
+    ));
+}
+
+#[test]
+fn unicode_identifiers_and_strings() {
+    let output = run(
+        "unicode_identifiers_and_strings",
+        "var 挨拶 = \"こんにちは 🌏\";\nvar cafe\u{301} = \"e\u{301}\";\nprint 挨拶;\nprint cafe\u{301} + \"!\";",
+    );
+    assert_eq!(output, "こんにちは 🌏\ne\u{301}!\n");
+}
+
+#[test]
+fn string_interpolation_stringifies_each_part() {
+    let output = run(
+        "string_interpolation_stringifies_each_part",
+        r#"
+        var name = "Ada";
+        var count = 2;
+        print "Hello ${name}, you have ${count + 1} items";
+        print "${true} ${nil} ${1.5} ${1 == 1} \${literal}";
+        "#,
+    );
+    assert_eq!(
+        output,
+        "Hello Ada, you have 3 items\ntrue Nil 1.5 true ${literal}\n"
+    );
+}
+
+#[test]
+fn interpolation_errors_point_inside_the_braces() {
+    let output = run(
+        "interpolation_errors_point_inside_the_braces",
+        r#"print "a ${1 + } b";"#,
+    );
+    assert!(output.starts_with("error at '}': Expect expression.\n --> line 1, column 16\n"));
+}
+
+#[test]
+fn arithmetic_and_bitwise_operators() {
+    let output = run(
+        "arithmetic_and_bitwise_operators",
+        r#"
+        print 7 % 3;
+        print 2 ** 10;
+        print -7 ~/ 2;
+        print 12 & 10;
+        print 12 | 3;
+        print 12 ^ 10;
+        print ~0;
+        print 1 << 10;
+        print -16 >> 2;
+        "#,
+    );
+    assert_eq!(output, "1\n1024\n-4\n8\n15\n6\n-1\n1024\n-4\n");
+}
+
+#[test]
+fn remainder_matches_floor_division() {
+    let output = run(
+        "remainder_matches_floor_division",
+        r#"
+        print -7 % 2;
+        print 7 % -2;
+        print -7 % -2;
+        print (-7 ~/ 2) * 2 + (-7 % 2);
+        print (7 ~/ -2) * -2 + (7 % -2);
+        "#,
+    );
+    assert_eq!(output, "1\n-1\n-1\n-7\n7\n");
+}
+
+#[test]
+fn operator_type_errors() {
+    assert_errors(
+        "operator_type_errors",
+        &[
+            ("print 1.5 & 1;", "error at '&': Operands must be integers."),
+            (
+                "print \"a\" % 2;",
+                "error at '%': Operands must be numbers.",
+            ),
+            ("print ~true;", "error at '~': Operand must be an integer."),
+            (
+                "print 1 << 64;",
+                "error at '<<': Shift amount must be between 0 and 63.",
+            ),
+            ("print 1 ~/ 0;", "error at '~/': Can't divide by Zero"),
+        ],
+    );
+}
+
+#[test]
+fn compound_assignment_and_updates() {
+    let output = run(
+        "compound_assignment_and_updates",
+        r#"
+        var i = 10;
+        i += 5; i -= 3; i *= 2; i /= 4; i %= 4;
+        print i;
+        var s = "a";
+        s += "b";
+        print s;
+        print i++;
+        print ++i;
+        print i--;
+        print --i;
+        {
+          var local = 1;
+          fun bump() { local += 1; return local++; }
+          print bump();
+          print local;
+        }
+        "#,
+    );
+    assert_eq!(output, "2\nab\n2\n4\n4\n2\n2\n3\n");
+}
+
+#[test]
+fn property_updates_evaluate_the_object_once() {
+    let output = run(
+        "property_updates_evaluate_the_object_once",
+        r#"
+        class Box {}
+        var box = Box();
+        box.n = 1;
+        var calls = 0;
+        fun get() { calls++; return box; }
+        get().n += 10;
+        get().n++;
+        print box.n;
+        print calls;
+        "#,
+    );
+    assert_eq!(output, "12\n2\n");
+}
+
+#[test]
+fn update_errors() {
+    assert_errors(
+        "update_errors",
+        &[
+            ("1 += 2;", "error at '+=': Invalid assignment target."),
+            (
+                "var s = \"a\"; s++;",
+                "error at '++': Operand of '++' must be a number.",
+            ),
+            (
+                "var n = nil; n.x--;",
+                "error at 'x': Only instances have fields.",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn list_literals_indexing_and_mutation() {
+    let output = run(
+        "list_literals_indexing_and_mutation",
+        r#"
+        var xs = [1, "two", [3, nil],];
+        print xs;
+        print xs[1];
+        print xs[2][0];
+        var alias = xs;
+        alias[0] = 10;
+        print xs[0];
+        xs[2][0] += 4;
+        print xs[2];
+        print xs[0]++;
+        print xs;
+        print [];
+        if ([]) print "empty lists are truthy";
+        "#,
+    );
+    assert_eq!(
+        output,
+        "[1, \"two\", [3, Nil]]\ntwo\n3\n10\n[7, Nil]\n10\n[11, \"two\", [7, Nil]]\n[]\nempty lists are truthy\n"
+    );
+}
+
+#[test]
+fn lists_compare_by_value() {
+    let output = run(
+        "lists_compare_by_value",
+        r#"
+        print [1, [2, "a"]] == [1, [2, "a"]];
+        print [1, 2] == [2, 1];
+        print [1] != [1, 1];
+        var a = [1];
+        a[0] = a;
+        print a;
+        print a == a;
+        "#,
+    );
+    assert_eq!(output, "true\nfalse\ntrue\n[[...]]\ntrue\n");
+}
+
+#[test]
+fn list_index_errors() {
+    assert_errors(
+        "list_index_errors",
+        &[
+            (
+                "[1, 2][2];",
+                "error at '[': List index 2 out of range for length 2.",
+            ),
+            (
+                "[1][-1] = 0;",
+                "error at '[': List index -1 out of range for length 1.",
+            ),
+            ("[1][0.5];", "error at '[': List index must be an integer."),
+            (
+                "[1][\"0\"];",
+                "error at '[': List index must be an integer.",
+            ),
+            (
+                "var n = 1; n[0];",
+                "error at '[': Only lists and maps can be indexed.",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn map_literals_indexing_and_mutation() {
+    let output = run(
+        "map_literals_indexing_and_mutation",
+        r#"
+        var config = {"name": "lox", "version": 1, "tags": ["a"], 2: true, nil: {},};
+        print config;
+        print config["name"];
+        print config[2];
+        config["version"] += 1;
+        config["new"] = false;
+        config["name"] = "LOX";
+        print config;
+        var empty = {};
+        if (empty) print "empty maps are truthy";
+        {"a": 1}["a"];
+        {}
+        print {"a": 1}["a"];
+        "#,
+    );
+    assert_eq!(
+        output,
+        "{\"name\": \"lox\", \"version\": 1, \"tags\": [\"a\"], 2: true, Nil: {}}\n\
+         lox\n\
+         true\n\
+         {\"name\": \"LOX\", \"version\": 2, \"tags\": [\"a\"], 2: true, Nil: {}, \"new\": false}\n\
+         empty maps are truthy\n\
+         1\n"
+    );
+}
+
+#[test]
+fn maps_compare_by_entries() {
+    let output = run(
+        "maps_compare_by_entries",
+        r#"
+        print {"a": 1, "b": [2]} == {"b": [2], "a": 1};
+        print {"a": 1} == {"a": 2};
+        print {0: "zero"}[-0];
+        var m = {};
+        m["self"] = m;
+        print m;
+        "#,
+    );
+    assert_eq!(output, "true\nfalse\nzero\n{\"self\": {...}}\n");
+}
+
+#[test]
+fn map_errors() {
+    assert_errors(
+        "map_errors",
+        &[
+            (
+                "var m = {\"a\": 1}; m[\"b\"];",
+                "error at '[': Undefined key \"b\".",
+            ),
+            (
+                "var m = {}; m[[1]] = 2;",
+                "error at '[': Map keys must be strings, numbers, booleans or nil.",
+            ),
+            (
+                "print {[]: 1};",
+                "error at '{': Map keys must be strings, numbers, booleans or nil.",
+            ),
+            (
+                "var big = 10 ** 400; var m = {}; m[big - big] = 1;",
+                "error at '[': Map keys can't be NaN.",
+            ),
+            (
+                "var m = {\"a\" 1};",
+                "error at '1': Expect ':' after map key.",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn for_in_over_builtin_collections_and_ranges() {
+    let output = run(
+        "for_in_over_builtin_collections_and_ranges",
+        r#"
+        for (var x in [1, 2, 3]) print x;
+        for (var key in {"a": 1, "b": 2}) print key;
+        for (var c in "hé") print c;
+        var n = 3;
+        for (var i in 0..n) print i;
+        for (var i in 5..5) print "never";
+        var in = [10];
+        for (var x in in) print x;
+        "#,
+    );
+    assert_eq!(output, "1\n2\n3\na\nb\nh\né\n0\n1\n2\n10\n");
+}
+
+#[test]
+fn for_in_control_flow_and_closures() {
+    let output = run(
+        "for_in_control_flow_and_closures",
+        r#"
+        for (var i in 0..10) {
+          if (i == 1) continue;
+          if (i == 3) break;
+          print i;
+        }
+        fun first(xs) {
+          for (var x in xs) return x;
+          return nil;
+        }
+        print first(["only"]);
+        var fns = {};
+        for (var i in 0..3) {
+          fun f() { return i; }
+          fns[i] = f;
+        }
+        print fns[0]();
+        print fns[2]();
+        "#,
+    );
+    assert_eq!(output, "0\n2\nonly\n0\n2\n");
+}
+
+#[test]
+fn for_in_over_objects_with_an_iterator() {
+    let output = run(
+        "for_in_over_objects_with_an_iterator",
+        r#"
+        class Countdown {
+          init(from) { this.from = from; }
+          iterator() { return CountdownIterator(this.from); }
+        }
+        class CountdownIterator {
+          init(n) { this.n = n; }
+          next() {
+            if (this.n == 0) return nil;
+            this.n = this.n - 1;
+            return this.n + 1;
+          }
+        }
+        for (var n in Countdown(3)) print n;
+        "#,
+    );
+    assert_eq!(output, "3\n2\n1\n");
+}
+
+#[test]
+fn for_in_errors() {
+    assert_errors(
+        "for_in_errors",
+        &[
+        (
+            "for (var x in 1) print x;",
+            "error at 'in': Can only iterate over lists, maps, strings, ranges and objects with an 'iterator' method.",
+        ),
+        (
+            "for (var x in 0..\"3\") print x;",
+            "error at 'in': Range bounds must be numbers.",
+        ),
+        (
+            "class A { iterator() { return 1; } } for (var x in A()) print x;",
+            "error at 'in': Iterator must have a 'next' method.",
+        ),
+        (
+            "for (var x in [1]) {} print x;",
+            "error at 'x': Undefined variable 'x'.",
+        ),
+        ],
+    );
+}
+
+#[test]
+fn lambdas_and_arrow_functions() {
+    let output = run(
+        "lambdas_and_arrow_functions",
+        r#"
+        var add = fun (a, b) { return a + b; };
+        print add(1, 2);
+        var square = (x) => x * x;
+        print square(4);
+        var answer = () => 42;
+        print answer();
+        fun apply(f, xs) {
+          for (var x in xs) print f(x);
+        }
+        apply((n) => n + 1, [1, 2]);
+        fun makeCounter() {
+          var i = 0;
+          return fun () { i++; return i; };
+        }
+        var counter = makeCounter();
+        counter();
+        print counter();
+        print (a, b) => a;
+        print fun () {}();
+        fun (x) { print x; }("called at once");
+        var grouped = (1 + 2) * 3;
+        print grouped;
+        "#,
+    );
+    assert_eq!(
+        output,
+        "3\n16\n42\n2\n3\n2\n<fn lambda>\nNil\ncalled at once\n9\n"
+    );
+}
+
+#[test]
+fn lambda_errors() {
+    assert_errors(
+        "lambda_errors",
+        &[
+            (
+                "var f = fun (a) { break; };",
+                "error at 'break': Can't use 'break' outside of a loop.",
+            ),
+            ("var f = (a, b) => ;", "error at ';': Expect expression."),
+            (
+                "var f = fun a() {};",
+                "error at 'a': Expect '(' after 'fun'.",
+            ),
+            (
+                "var f = (x) => x.y; f(1);",
+                "error at 'y': Only instances have properties.",
+            ),
+        ],
+    );
+}
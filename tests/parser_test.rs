@@ -1,9 +1,218 @@
 #[cfg(test)]
 mod tests {
     use lox_lang::parser::parser::Parser;
+    use lox_lang::scanner::Scanner;
+    use lox_lang::stmt::StmtExpr;
 
-    use super::*;
+    fn parse_expression(source: &str) -> String {
+        let tokens = Scanner::new(source.to_string()).scan_tokens();
+        let (statements, errors) = Parser::new(tokens).parse();
+        assert!(errors.is_empty(), "unexpected parse errors: {:?}", errors);
+
+        match &statements[0].expresstion {
+            StmtExpr::Expresstion(stmt) => stmt.expresstion.kind.to_string(),
+            other => panic!("expected an expression statement, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ternary_is_right_associative() {
+        assert_eq!(
+            parse_expression("a ? b : c ? d : e;"),
+            "(ternary (Variable a) (Variable b) (ternary (Variable c) (Variable d) (Variable e)))"
+        );
+    }
+
+    #[test]
+    fn ternary_binds_looser_than_or() {
+        assert_eq!(
+            parse_expression("a or b ? c : d;"),
+            "(ternary (or (Variable a) (Variable b)) (Variable c) (Variable d))"
+        );
+    }
+
+    #[test]
+    fn separator_is_left_associative_and_lowest_precedence() {
+        assert_eq!(
+            parse_expression("a = 1, b = 2, c;"),
+            "(separator (separator (assign a 1) (assign b 2)) (Variable c))"
+        );
+    }
+
+    #[test]
+    fn call_arguments_are_not_separator_expressions() {
+        assert_eq!(
+            parse_expression("f(a, (b, c));"),
+            "(call (Variable f) (Variable a) (group (separator (Variable b) (Variable c))))"
+        );
+    }
+
+    #[test]
+    fn reports_every_syntax_error_and_keeps_the_rest() {
+        let source = "var a = ;\nprint 1 +;\nprint a\nvar b = 2;\n(1) = 2;\nprint b;";
+        let tokens = Scanner::new(source.to_string()).scan_tokens();
+        let (statements, errors) = Parser::new(tokens).parse();
+
+        let found: Vec<(usize, &str)> = errors
+            .iter()
+            .map(|err| (err.token.line, err.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "Expect expression."),
+                (2, "Expect expression."),
+                (4, "Expect ';' after value."),
+                (5, "Invalid assignment target."),
+            ]
+        );
+        // `var b = 2;`, `(1) = 2;` and `print b;` survive recovery.
+        assert_eq!(statements.len(), 3);
+    }
+
+    #[test]
+    fn errors_inside_blocks_resume_at_the_next_statement() {
+        let source = "{\n  var a = ;\n  print 1;\n  if (a { print 2; }\n  print 3;\n}\nprint 4;";
+        let tokens = Scanner::new(source.to_string()).scan_tokens();
+        let (statements, errors) = Parser::new(tokens).parse();
+
+        let lines: Vec<usize> = errors.iter().map(|err| err.token.line).collect();
+        assert_eq!(lines, vec![2, 4]);
+        assert_eq!(statements.len(), 2);
+        match &statements[0].expresstion {
+            StmtExpr::Block(block) => assert_eq!(block.statements.len(), 2),
+            other => panic!("expected a block, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn interpolated_strings_become_a_single_node() {
+        assert_eq!(
+            parse_expression(r#""Hello ${name}, you have ${count + 1} items";"#),
+            r#"(interpolate "Hello " (Variable name) ", you have " (+ (Variable count) 1) " items")"#
+        );
+    }
+
+    #[test]
+    fn interpolation_can_nest_strings_and_braces() {
+        assert_eq!(
+            parse_expression(r#""a ${ f("}", "${b}") } c";"#),
+            r#"(interpolate "a " (call (Variable f) "}" (interpolate (Variable b))) " c")"#
+        );
+    }
+
+    #[test]
+    fn power_is_right_associative_and_binds_tighter_than_unary() {
+        assert_eq!(parse_expression("-2 ** 3 ** 2;"), "(- (** 2 (** 3 2)))");
+        assert_eq!(parse_expression("2 ** -1;"), "(** 2 (- 1))");
+    }
+
+    #[test]
+    fn factor_operators_are_left_associative() {
+        assert_eq!(parse_expression("8 / 4 % 3 ~/ 2;"), "(~/ (% (/ 8 4) 3) 2)");
+    }
+
+    #[test]
+    fn bitwise_precedence_sits_between_comparison_and_term() {
+        assert_eq!(
+            parse_expression("a | b ^ c & d << 1 + 2 == e;"),
+            "(== (| (Variable a) (^ (Variable b) (& (Variable c) (<< (Variable d) (+ 1 2))))) (Variable e))"
+        );
+    }
+
+    #[test]
+    fn compound_assignment_is_right_associative() {
+        assert_eq!(
+            parse_expression("a += b -= 2;"),
+            "(+= (Variable a) (-= (Variable b) 2))"
+        );
+        assert_eq!(parse_expression("p.x %= 3;"), "(%= (get (Variable p) x) 3)");
+    }
+
+    #[test]
+    fn prefix_and_postfix_updates() {
+        assert_eq!(
+            parse_expression("-a.b++;"),
+            "(- (postfix ++ (get (Variable a) b)))"
+        );
+        assert_eq!(
+            parse_expression("--a * 2;"),
+            "(* (prefix -- (Variable a)) 2)"
+        );
+    }
+
+    #[test]
+    fn list_literals_and_index_expressions() {
+        assert_eq!(
+            parse_expression("[1, a[0], [],];"),
+            "(list 1 (index (Variable a) 0) (list))"
+        );
+        assert_eq!(
+            parse_expression("a[i][j] = f()[0];"),
+            "(index-set (index (Variable a) (Variable i)) (Variable j) (index (call (Variable f)) 0))"
+        );
+        assert_eq!(
+            parse_expression("a[0] *= 2;"),
+            "(*= (index (Variable a) 0) 2)"
+        );
+    }
+
+    #[test]
+    fn map_literals_in_expressions_and_statements() {
+        assert_eq!(
+            parse_expression(r#"x = {"a": 1, b: {}, 2: [],};"#),
+            r#"(assign x (map "a" 1 (Variable b) (map) 2 (list)))"#
+        );
+        assert_eq!(
+            parse_expression(r#"{"a": 1}["a"];"#),
+            r#"(index (map "a" 1) "a")"#
+        );
+        assert_eq!(
+            parse_expression(r#"{"a" + "b": 1, (k): 2};"#),
+            r#"(map (+ "a" "b") 1 (group (Variable k)) 2)"#
+        );
+    }
+
+    #[test]
+    fn braces_at_statement_start_are_still_blocks() {
+        let tokens =
+            Scanner::new("{} { a ? b : c; } { a = 1; } { print a; }".to_string()).scan_tokens();
+        let (statements, errors) = Parser::new(tokens).parse();
+
+        assert!(errors.is_empty(), "unexpected parse errors: {:?}", errors);
+        assert!(statements
+            .iter()
+            .all(|stmt| matches!(stmt.expresstion, StmtExpr::Block(_))));
+    }
+
+    #[test]
+    fn for_in_with_a_range() {
+        let tokens = Scanner::new("for (var i in 0..n - 1) print i;".to_string()).scan_tokens();
+        let (statements, errors) = Parser::new(tokens).parse();
+
+        assert!(errors.is_empty(), "unexpected parse errors: {:?}", errors);
+        match &statements[0].expresstion {
+            StmtExpr::ForIn(stmt) => {
+                assert_eq!(stmt.name.lexeme, "i");
+                assert_eq!(stmt.iterable.kind.to_string(), "0");
+                assert_eq!(
+                    stmt.range_end.as_ref().unwrap().kind.to_string(),
+                    "(- (Variable n) 1)"
+                );
+            }
+            other => panic!("expected a for-in loop, got {:?}", other),
+        }
+    }
 
     #[test]
-    fn test_name() {}
+    fn lambdas_and_arrow_functions_are_expressions() {
+        assert_eq!(
+            parse_expression("f(fun (a, b) { return a; }, (x) => x, () => 1);"),
+            "(call (Variable f) (lambda a b) (lambda x) (lambda))"
+        );
+        assert_eq!(
+            parse_expression("(a) + (b, c);"),
+            "(+ (group (Variable a)) (group (separator (Variable b) (Variable c))))"
+        );
+    }
 }
@@ -0,0 +1,298 @@
+#[cfg(test)]
+mod tests {
+    use lox_lang::scanner::Scanner;
+    use lox_lang::token::{Span, TokenLiteral, TokenType};
+
+    #[test]
+    fn tokens_record_line_column_and_offsets() {
+        let tokens = Scanner::new("var a = 1;\n  print a;".to_string()).scan_tokens();
+
+        let print = &tokens[5];
+        assert_eq!(print.token_type, TokenType::PRINT);
+        assert_eq!(print.line, 2);
+        assert_eq!(
+            print.span,
+            Span {
+                start: 13,
+                end: 18,
+                column: 3
+            }
+        );
+
+        let eof = tokens.last().unwrap();
+        assert_eq!(eof.token_type, TokenType::EOF);
+        assert_eq!((eof.line, eof.span.column), (2, 11));
+    }
+
+    #[test]
+    fn multi_line_string_starts_on_its_first_line() {
+        let tokens = Scanner::new("\"a\nb\" x".to_string()).scan_tokens();
+
+        assert_eq!((tokens[0].line, tokens[0].span.column), (1, 1));
+        assert_eq!((tokens[1].line, tokens[1].span.column), (2, 4));
+    }
+
+    #[test]
+    fn non_ascii_strings_keep_their_text_and_columns_count_characters() {
+        let mut scanner = Scanner::new("\"héllo 日本\" x".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].lexeme, "\"héllo 日本\"");
+        assert_eq!(tokens[1].lexeme, "x");
+        assert_eq!(tokens[1].span.column, 12);
+        assert_eq!(tokens[1].span.start, "\"héllo 日本\" ".len());
+        assert!(scanner.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn non_ascii_outside_strings_is_an_error_not_a_panic() {
+        let mut scanner = Scanner::new("a § b".to_string());
+        let tokens = scanner.scan_tokens();
+
+        let lexemes: Vec<&str> = tokens.iter().map(|token| token.lexeme.as_str()).collect();
+        assert_eq!(lexemes, vec!["a", "b", ""]);
+        let error = scanner.diagnostics.iter().next().unwrap();
+        assert_eq!(error.message, "Unexpected character.");
+        assert_eq!((error.primary.column, error.primary.length), (3, 1));
+    }
+
+    #[test]
+    fn identifiers_follow_unicode_xid_rules() {
+        // "e" followed by U+0301 COMBINING ACUTE ACCENT continues the identifier.
+        let source = "名前 _x1 cafe\u{301} über 🎉";
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens();
+
+        let identifiers: Vec<&str> = tokens
+            .iter()
+            .filter(|token| token.token_type == TokenType::IDENTIFIER)
+            .map(|token| token.lexeme.as_str())
+            .collect();
+        assert_eq!(identifiers, vec!["名前", "_x1", "cafe\u{301}", "über"]);
+
+        // Emoji are not identifier characters.
+        let error = scanner.diagnostics.iter().next().unwrap();
+        assert_eq!(error.message, "Unexpected character.");
+        assert_eq!(error.primary.column, 19);
+    }
+
+    #[test]
+    fn string_literals_round_trip_utf8() {
+        let text = "🎉 日本語 e\u{301} 👩‍👩‍👧";
+        let tokens = Scanner::new(format!("\"{}\"", text)).scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::STRING);
+        match &tokens[0].literal {
+            Some(TokenLiteral::Text(literal)) => assert_eq!(literal, text),
+            other => panic!("expected a string literal, got {:?}", other),
+        }
+    }
+
+    fn string_literal(source: &str) -> String {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens();
+        assert!(scanner.diagnostics.is_empty());
+        match &tokens[0].literal {
+            Some(TokenLiteral::Text(literal)) => literal.clone(),
+            other => panic!("expected a string literal, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn string_escapes_are_decoded() {
+        assert_eq!(
+            string_literal(r#""a\nb\tc \\ \"q\" \u{48}\u{1F600}""#),
+            "a\nb\tc \\ \"q\" H😀"
+        );
+    }
+
+    #[test]
+    fn invalid_escapes_are_reported_where_they_appear() {
+        let mut scanner = Scanner::new("\"first\nsecond \\q\nthird \\u{110000}\"".to_string());
+        let tokens = scanner.scan_tokens();
+
+        let errors: Vec<(&str, usize, usize, usize)> = scanner
+            .diagnostics
+            .iter()
+            .map(|error| {
+                let label = &error.primary;
+                (
+                    error.message.as_str(),
+                    label.line,
+                    label.column,
+                    label.length,
+                )
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                ("Invalid escape sequence '\\q'.", 2, 8, 2),
+                ("Invalid unicode escape.", 3, 7, 10),
+            ]
+        );
+        assert_eq!(tokens[0].token_type, TokenType::STRING);
+    }
+
+    #[test]
+    fn raw_strings_skip_escapes_and_span_lines() {
+        let source = "\"\"\"say \"hi\"\\n\nnext\"\"\" x";
+        assert_eq!(string_literal(source), "say \"hi\"\\n\nnext");
+
+        let tokens = Scanner::new(source.to_string()).scan_tokens();
+        assert_eq!((tokens[1].line, tokens[1].span.column), (2, 9));
+    }
+
+    #[test]
+    fn unterminated_raw_string_points_at_its_start() {
+        let mut scanner = Scanner::new("x \"\"\"open\nnever closed".to_string());
+        scanner.scan_tokens();
+
+        let error = scanner.diagnostics.iter().next().unwrap();
+        assert_eq!(error.message, "Unterminated raw string.");
+        assert_eq!((error.primary.line, error.primary.column), (1, 3));
+    }
+
+    fn numbers(source: &str) -> Vec<f64> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens();
+        assert!(scanner.diagnostics.is_empty());
+        tokens
+            .iter()
+            .filter_map(|token| match token.literal {
+                Some(TokenLiteral::Float(value)) => Some(value),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extended_number_literals() {
+        assert_eq!(
+            numbers("0xFF 0b1010 0o755 1.5e-3 2E3 1_000_000 0xdead_BEEF 12.5"),
+            vec![
+                255.0,
+                10.0,
+                493.0,
+                0.0015,
+                2000.0,
+                1000000.0,
+                3735928559.0,
+                12.5
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_numbers_are_scan_errors() {
+        for (source, message) in [
+            ("0x;", "Expect digits after '0x'."),
+            ("0b102;", "Invalid digit '2' in binary literal."),
+            ("0o8;", "Invalid digit '8' in octal literal."),
+            ("1e+;", "Expect digits in exponent."),
+            ("1__0;", "A '_' separator must be between two digits."),
+            ("1_;", "A '_' separator must be between two digits."),
+            ("12abc;", "Invalid suffix 'abc' on number literal."),
+            (
+                "0x1_0000_0000_0000_0000;",
+                "The hexadecimal literal is too large.",
+            ),
+        ] {
+            let mut scanner = Scanner::new(source.to_string());
+            let tokens = scanner.scan_tokens();
+
+            let errors: Vec<&str> = scanner
+                .diagnostics
+                .iter()
+                .map(|error| error.message.as_str())
+                .collect();
+            assert_eq!(errors, vec![message], "scanning {}", source);
+            let error = scanner.diagnostics.iter().next().unwrap();
+            assert_eq!(
+                error.primary.length,
+                source.len() - 1,
+                "scanning {}",
+                source
+            );
+            // The literal still becomes a single NUMBER token.
+            assert_eq!(tokens[0].token_type, TokenType::NUMBER);
+            assert_eq!(tokens[1].token_type, TokenType::SEMICOLON);
+        }
+    }
+
+    #[test]
+    fn block_comments_nest_and_count_lines() {
+        let mut scanner = Scanner::new("a /* x/y */ b /* 1 /* 2\n */ 3\n*/ c".to_string());
+        let tokens = scanner.scan_tokens();
+
+        assert!(scanner.diagnostics.is_empty());
+        let found: Vec<(&str, usize)> = tokens
+            .iter()
+            .map(|token| (token.lexeme.as_str(), token.line))
+            .collect();
+        assert_eq!(found, vec![("a", 1), ("b", 1), ("c", 3), ("", 3)]);
+        assert_eq!(tokens[2].span.column, 4);
+    }
+
+    #[test]
+    fn unterminated_block_comment_points_at_its_opening() {
+        let mut scanner = Scanner::new("a\n  /* one /* two */\nb".to_string());
+        let tokens = scanner.scan_tokens();
+
+        let error = scanner.diagnostics.iter().next().unwrap();
+        assert_eq!(error.message, "Unterminated block comment.");
+        assert_eq!(
+            (
+                error.primary.line,
+                error.primary.column,
+                error.primary.length
+            ),
+            (2, 3, 2)
+        );
+        assert_eq!(tokens.len(), 2);
+    }
+
+    #[test]
+    fn range_dots_are_not_a_decimal_point() {
+        let tokens = Scanner::new("0..10 a.b".to_string()).scan_tokens();
+
+        let types: Vec<TokenType> = tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::NUMBER,
+                TokenType::DOTDOT,
+                TokenType::NUMBER,
+                TokenType::IDENTIFIER,
+                TokenType::DOT,
+                TokenType::IDENTIFIER,
+                TokenType::EOF
+            ]
+        );
+    }
+
+    #[test]
+    fn tilde_before_a_comment_is_not_floor_division() {
+        let tokens = Scanner::new("~/* c */ 5 x ~// note\n~/ 2".to_string()).scan_tokens();
+
+        let types: Vec<TokenType> = tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::TILDE,
+                TokenType::NUMBER,
+                TokenType::IDENTIFIER,
+                TokenType::TILDE,
+                TokenType::TILDESLASH,
+                TokenType::NUMBER,
+                TokenType::EOF
+            ]
+        );
+    }
+}